@@ -0,0 +1,343 @@
+use std::{env, fmt, path::PathBuf, sync::Arc};
+
+use crate::{
+    check::{check_var, stale_files},
+    config::{Config, Discovery, Indent, SortKey},
+    diagnostics::{Diagnostic, Severity},
+    emit::{Bindings, Declarations, Emitter, JsonManifest},
+    error::Error,
+    parse::parse_functions,
+    permissions::{obsolete_permissions, permission_files, write_permissions},
+    write::write_if_changed,
+    TauriVersion,
+};
+
+/// Configures and runs the generation of the declaration file.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// fn main() {
+///     tauri_named_invoke::Builder::new()
+///         .out_dir("ui")
+///         .file_name("commands.d.ts")
+///         .build()
+///         .unwrap();
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct Builder {
+    config: Config,
+    emitters: Vec<Arc<dyn Emitter>>,
+}
+
+impl fmt::Debug for Builder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Builder")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The directory where the file will be generated, relative to the crate root.
+    /// Defaults to the crate root.
+    pub fn out_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.out_dir = dir.into();
+        self
+    }
+
+    /// The name of the generated file. Defaults to `invoke.d.ts`.
+    pub fn file_name(mut self, name: impl Into<String>) -> Self {
+        self.config.file_name = name.into();
+        self
+    }
+
+    /// How the files to scan for commands are found. Defaults to [`Discovery::Files`].
+    pub fn discovery(mut self, discovery: Discovery) -> Self {
+        self.config.discovery = discovery;
+        self
+    }
+
+    /// The directory searched for commands, relative to the crate root.
+    /// Defaults to the crate root.
+    ///
+    /// Files and directories listed in `.gitignore` and `.ignore` files are skipped.
+    /// Not used with [`Discovery::ModuleTree`], as well as the include and exclude globs.
+    pub fn scan_root(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.scan_root = dir.into();
+        self
+    }
+
+    /// Scans only the `.rs` files matching the glob, relative to the scan root,
+    /// e.g. `src/commands/**`. Can be called multiple times.
+    pub fn include(mut self, glob: impl Into<String>) -> Self {
+        self.config.include.push(glob.into());
+        self
+    }
+
+    /// Skips the files and directories matching the glob, relative to the scan root,
+    /// e.g. `**/fixtures`. Can be called multiple times.
+    pub fn exclude(mut self, glob: impl Into<String>) -> Self {
+        self.config.exclude.push(glob.into());
+        self
+    }
+
+    /// Whether to skip `target`, `node_modules` and `dist` directories. Enabled by default.
+    pub fn default_excludes(mut self, enabled: bool) -> Self {
+        self.config.default_excludes = enabled;
+        self
+    }
+
+    /// The module whose `invoke` function is declared. Defaults to the module
+    /// of the Tauri version, see [`TauriVersion::module`].
+    pub fn module(mut self, module: impl Into<String>) -> Self {
+        self.config.module = Some(module.into());
+        self
+    }
+
+    /// The Tauri version to generate declarations for. By default it's detected
+    /// from the `tauri` dependency in `Cargo.toml` or `Cargo.lock`.
+    pub fn tauri_version(mut self, version: TauriVersion) -> Self {
+        self.config.tauri_version = Some(version);
+        self
+    }
+
+    /// Leaves the parameters of the given type out of the command arguments.
+    ///
+    /// Parameters that Tauri injects into commands (`State`, `AppHandle`, `Window`,
+    /// `WebviewWindow`, `Webview`, `Request`, ...) are always left out. Use this for
+    /// your own types implementing [`CommandArg`], e.g. a wrapper extracting a database
+    /// connection from the state. A type is given by its name or by its path,
+    /// like `Db` or `crate::auth::User`.
+    ///
+    /// [`CommandArg`]: https://docs.rs/tauri/latest/tauri/ipc/trait.CommandArg.html
+    pub fn extractor(mut self, path: impl Into<String>) -> Self {
+        self.config.extractors.push(path.into());
+        self
+    }
+
+    /// Whether to leave out the commands that aren't registered with `tauri::generate_handler!`.
+    /// Enabled by default.
+    ///
+    /// Either way, a warning is emitted for every command that isn't registered and
+    /// for every registered command whose definition wasn't found. If there's no
+    /// `generate_handler!` invocation in the sources, all commands are included.
+    pub fn registered_only(mut self, enabled: bool) -> Self {
+        self.config.registered_only = enabled;
+        self
+    }
+
+    /// The indentation of the generated file. Defaults to 4 spaces.
+    pub fn indent(mut self, indent: Indent) -> Self {
+        self.config.indent = indent;
+        self
+    }
+
+    /// Whether the crate is a Tauri plugin. Disabled by default.
+    ///
+    /// The commands of a plugin are invoked as `plugin:<name>|<command>`, the name being
+    /// the one passed to `tauri::plugin::Builder::new` or set with [`Builder::plugin_name`].
+    /// Besides the declaration file, a module of typed functions calling the commands
+    /// is generated in `guest-js/bindings.ts`, to be exported from the JavaScript package of the plugin.
+    /// Its path can be changed with [`Builder::bindings`].
+    pub fn plugin(mut self, enabled: bool) -> Self {
+        self.config.plugin = enabled;
+        self
+    }
+
+    /// The name of the plugin, when it can't be read from `tauri::plugin::Builder::new`.
+    /// Enables [`Builder::plugin`].
+    pub fn plugin_name(mut self, name: impl Into<String>) -> Self {
+        self.config.plugin = true;
+        self.config.plugin_name = Some(name.into());
+        self
+    }
+
+    /// Generates a TypeScript module with a function calling each command, at the given path
+    /// relative to the crate root, e.g. `ui/src/commands.ts`:
+    ///
+    /// ```typescript
+    /// export type GetWeatherArgs = {
+    ///     city: string;
+    /// };
+    ///
+    /// export async function getWeather(args: GetWeatherArgs, options?: InvokeOptions): Promise<string> {
+    ///     return await invoke<string>('get_weather', args, options);
+    /// }
+    /// ```
+    ///
+    /// Functions are named in camelCase, with a trailing `_` if the name is reserved in JavaScript,
+    /// like `delete_`, or taken by an import, a type or another function of the module, like `invoke_`
+    /// or `getWeather_` for a `getWeather` command next to `get_weather`.
+    /// The module declares the types used by the commands as well.
+    pub fn bindings(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.bindings = Some(path.into());
+        self
+    }
+
+    /// Whether to generate the declaration file augmenting `invoke`. Enabled by default.
+    ///
+    /// Disable it when the module of [`Builder::bindings`] is used instead.
+    pub fn declarations(mut self, enabled: bool) -> Self {
+        self.config.declarations = enabled;
+        self
+    }
+
+    /// Writes a JSON manifest of the commands at the given path relative to the crate root,
+    /// e.g. `target/commands.json`, for tools that need the commands and their signatures.
+    ///
+    /// The manifest has a `version` of its format, increased on every breaking change,
+    /// and lists the `commands` with their `name`, source `file` and `line`, `docs`, `params`,
+    /// `returns` type, `platforms` and `cfg` predicates, along with the serializable `types` they use.
+    pub fn manifest(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.manifest = Some(path.into());
+        self
+    }
+
+    /// Adds an emitter generating more files from the commands, e.g. bindings
+    /// for another language. Can be called multiple times.
+    pub fn emitter(mut self, emitter: impl Emitter + 'static) -> Self {
+        self.emitters.push(Arc::new(emitter));
+        self
+    }
+
+    /// Whether to write the `allow-<command>` and `deny-<command>` permissions of every command
+    /// to `permissions/autogenerated/commands/<command>.toml`, with a default permission set
+    /// allowing all of them in `permissions/autogenerated/default.toml`. Disabled by default.
+    ///
+    /// The files are kept in sync with the commands on each build. As there can be only one
+    /// default permission set, a hand-written `[default]` has to be removed.
+    /// Permissions exist since Tauri 2, for Tauri 1 nothing is written.
+    pub fn permissions(mut self, enabled: bool) -> Self {
+        self.config.permissions = enabled;
+        self
+    }
+
+    /// The settings, to pass to [`scan`](crate::scan) and [`render`](crate::render).
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Whether to check that the generated files are up to date instead of writing them.
+    /// Disabled by default. Setting the `TAURI_NAMED_INVOKE_CHECK` environment variable to `1`
+    /// enables it as well, even if it's disabled here.
+    ///
+    /// In check mode, [`Builder::build`] returns an error with a unified diff of the files
+    /// that differ from what would be generated, or that would be removed like the permission files
+    /// of commands that are gone, e.g. to make sure in CI that the committed declaration file is
+    /// in sync with the commands.
+    pub fn check(mut self, enabled: bool) -> Self {
+        self.config.check = enabled;
+        self
+    }
+
+    /// The order of the commands in the generated files. Defaults to [`SortKey::Name`].
+    pub fn sort_by(mut self, key: SortKey) -> Self {
+        self.config.sort_by = key;
+        self
+    }
+
+    /// Whether to fail the build when warnings are found, e.g. a command parameter
+    /// typed as `any` because its type can't be described. Disabled by default.
+    pub fn strict(mut self, enabled: bool) -> Self {
+        self.config.strict = enabled;
+        self
+    }
+
+    /// Generates the file.
+    ///
+    /// Files whose content hasn't changed aren't rewritten, and the others are replaced atomically,
+    /// so that watchers never see them half-written.
+    /// The problems found in the sources are printed as cargo warnings pointing to the file and line,
+    /// like `src/commands.rs:42: ...`. Source files that can't be read or parsed are skipped with a warning.
+    /// An [`Error`] is returned if the settings are invalid, if `Cargo.toml` can't be parsed,
+    /// if a file can't be written, in check mode if the files are out of date
+    /// and in strict mode if there are warnings.
+    pub fn build(self) -> Result<(), Error> {
+        let manifest_dir = manifest_dir()?;
+
+        let mut model = parse_functions(&manifest_dir, &self.config)?;
+
+        let mut emitters = Vec::<Arc<dyn Emitter>>::new();
+        if self.config.declarations {
+            emitters.push(Arc::new(Declarations));
+        }
+        let bindings = self.config.bindings.clone().or_else(|| {
+            model
+                .plugin
+                .as_ref()
+                .map(|_| PathBuf::from("guest-js").join("bindings.ts"))
+        });
+        if let Some(path) = bindings {
+            emitters.push(Arc::new(Bindings { path }));
+        }
+        if let Some(path) = self.config.manifest.clone() {
+            emitters.push(Arc::new(JsonManifest { path }));
+        }
+        emitters.extend(self.emitters.iter().cloned());
+
+        let permissions = match (self.config.permissions, model.tauri_version) {
+            (false, _) => Vec::new(),
+            (true, TauriVersion::V1) => {
+                model.diagnostics.push(Diagnostic::warning(
+                    "Tauri 1 has no permissions, the permission files are not written",
+                ));
+                Vec::new()
+            }
+            (true, TauriVersion::V2) => permission_files(&model),
+        };
+
+        for diagnostic in &model.diagnostics {
+            diagnostic.print();
+        }
+        let failed = model
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| self.config.strict || diagnostic.severity == Severity::Error)
+            .cloned()
+            .collect::<Vec<_>>();
+        if !failed.is_empty() {
+            return Err(Error::Diagnostics(failed));
+        }
+
+        let mut files = Vec::new();
+        for emitter in emitters {
+            files.extend(emitter.emit(&model, &self.config).map_err(Error::Emit)?);
+        }
+
+        if self.config.check || check_var() {
+            let obsolete = match permissions.is_empty() {
+                true => Vec::new(),
+                false => obsolete_permissions(&manifest_dir, &permissions)?,
+            };
+            return match stale_files(&manifest_dir, files.iter().chain(&permissions), &obsolete) {
+                Some(diff) => Err(Error::Stale { diff }),
+                None => Ok(()),
+            };
+        }
+
+        for file in files {
+            write_if_changed(&manifest_dir.join(&file.path), &file.content)?;
+        }
+        if !permissions.is_empty() {
+            write_permissions(&manifest_dir, &permissions)?;
+        }
+        Ok(())
+    }
+}
+
+/// The root of the crate being built, set by cargo for build scripts.
+pub(crate) fn manifest_dir() -> Result<PathBuf, Error> {
+    env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .ok_or_else(|| {
+            Error::config(
+                "`CARGO_MANIFEST_DIR` is not set, the generation must run in a build script",
+            )
+        })
+}
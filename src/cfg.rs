@@ -0,0 +1,489 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt,
+};
+
+use serde::Serialize;
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    AttrStyle, Attribute, Ident, LitStr, Meta, Token,
+};
+
+/// The operating systems Tauri apps run on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum Platform {
+    Linux,
+    Macos,
+    Windows,
+    Ios,
+    Android,
+}
+
+impl Platform {
+    /// All the platforms, in the order they are listed in the output.
+    pub const ALL: [Platform; 5] = [
+        Platform::Linux,
+        Platform::Macos,
+        Platform::Windows,
+        Platform::Ios,
+        Platform::Android,
+    ];
+
+    /// The value of `target_os` on the platform.
+    pub fn target_os(self) -> &'static str {
+        match self {
+            Platform::Linux => "linux",
+            Platform::Macos => "macos",
+            Platform::Windows => "windows",
+            Platform::Ios => "ios",
+            Platform::Android => "android",
+        }
+    }
+
+    fn target_family(self) -> &'static str {
+        match self {
+            Platform::Windows => "windows",
+            _ => "unix",
+        }
+    }
+
+    fn is_mobile(self) -> bool {
+        matches!(self, Platform::Ios | Platform::Android)
+    }
+}
+
+/// Flags that rustc sets by itself, cargo lists them only when they're enabled.
+const BUILTIN_FLAGS: &[&str] = &[
+    "debug_assertions",
+    "doc",
+    "doctest",
+    "miri",
+    "overflow_checks",
+    "proc_macro",
+    "test",
+    "ub_checks",
+];
+
+/// The configuration of the crate being built, as cargo passes it to build scripts
+/// through `CARGO_FEATURE_*` and `CARGO_CFG_*` environment variables.
+///
+/// The predicates on the operating system (`target_os`, `target_family`, `unix` and `windows`)
+/// are evaluated for each [`Platform`] instead, so that the declarations cover all of them
+/// whatever the target of the build. So are `desktop` and `mobile`, which `tauri-build` sets
+/// for the crate with `cargo:rustc-cfg` and build scripts never see.
+#[derive(Debug, Default)]
+pub(crate) struct Cfg {
+    /// Enabled features, uppercased with `-` replaced by `_` like in the variable names.
+    features: HashSet<String>,
+    /// `target_os` => `["linux"]`, `unix` => `[]`, ...
+    options: HashMap<String, Vec<String>>,
+    /// The flags that are neither set nor known, evaluated as disabled.
+    unknown_flags: RefCell<BTreeSet<String>>,
+}
+
+impl Cfg {
+    pub(crate) fn from_env() -> Self {
+        let mut cfg = Cfg::default();
+        for (key, value) in std::env::vars() {
+            if let Some(feature) = key.strip_prefix("CARGO_FEATURE_") {
+                cfg.features.insert(feature.to_string());
+            } else if let Some(option) = key.strip_prefix("CARGO_CFG_") {
+                let values = value
+                    .split(',')
+                    .filter(|value| !value.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                cfg.options.insert(option.to_ascii_lowercase(), values);
+            }
+        }
+        cfg
+    }
+
+    /// Whether an item with these attributes is compiled for any platform.
+    pub(crate) fn is_enabled(&self, attrs: &[Attribute]) -> bool {
+        !self.platforms(attrs).is_empty()
+    }
+
+    /// The platforms for which an item with these attributes is compiled,
+    /// i.e. all its `#[cfg]` predicates hold, `cfg_attr` included.
+    pub(crate) fn platforms(&self, attrs: &[Attribute]) -> Vec<Platform> {
+        Platform::ALL
+            .into_iter()
+            .filter(|&platform| {
+                self.expand_attrs_on(attrs, platform)
+                    .iter()
+                    .filter(|attr| attr.path().is_ident("cfg"))
+                    // Keep what can't be understood.
+                    .filter_map(|attr| attr.parse_args::<Predicate>().ok())
+                    .all(|predicate| self.eval(&predicate, platform))
+            })
+            .collect()
+    }
+
+    /// Replaces `#[cfg_attr(predicate, attrs...)]` with `attrs` if the predicate holds
+    /// for any platform, or removes it otherwise. For the attributes that differ between
+    /// platforms, see [`Cfg::expand_attrs_on`] and [`Cfg::platform_attrs`].
+    pub(crate) fn expand_attrs(&self, attrs: &[Attribute]) -> Vec<Attribute> {
+        self.expand_attrs_with(attrs, &|predicate| {
+            Platform::ALL
+                .into_iter()
+                .any(|platform| self.eval(predicate, platform))
+        })
+    }
+
+    /// Replaces `#[cfg_attr(predicate, attrs...)]` with `attrs` if the predicate holds
+    /// on the platform, or removes it otherwise.
+    pub(crate) fn expand_attrs_on(
+        &self,
+        attrs: &[Attribute],
+        platform: Platform,
+    ) -> Vec<Attribute> {
+        self.expand_attrs_with(attrs, &|predicate| self.eval(predicate, platform))
+    }
+
+    /// The attributes of the `cfg_attr`s whose predicate holds on some platforms only,
+    /// like `#[cfg_attr(windows, path = "windows.rs")]`.
+    pub(crate) fn platform_attrs(&self, attrs: &[Attribute]) -> Vec<Attribute> {
+        let mut found = Vec::new();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("cfg_attr")) {
+            let Ok(cfg_attr) = attr.parse_args::<CfgAttr>() else {
+                continue;
+            };
+            let platforms = Platform::ALL
+                .into_iter()
+                .filter(|&platform| self.eval(&cfg_attr.predicate, platform))
+                .count();
+            let attrs = cfg_attr.into_attrs(attr);
+            match platforms {
+                0 => {}
+                _ if platforms == Platform::ALL.len() => found.extend(self.platform_attrs(&attrs)),
+                _ => found.extend(self.expand_attrs(&attrs)),
+            }
+        }
+        found
+    }
+
+    fn expand_attrs_with(
+        &self,
+        attrs: &[Attribute],
+        holds: &dyn Fn(&Predicate) -> bool,
+    ) -> Vec<Attribute> {
+        let mut expanded = Vec::new();
+        for attr in attrs {
+            if !attr.path().is_ident("cfg_attr") {
+                expanded.push(attr.clone());
+                continue;
+            }
+            let Ok(cfg_attr) = attr.parse_args::<CfgAttr>() else {
+                continue;
+            };
+            if holds(&cfg_attr.predicate) {
+                expanded.extend(self.expand_attrs_with(&cfg_attr.into_attrs(attr), holds));
+            }
+        }
+        expanded
+    }
+
+    /// The flags found in predicates that are neither set for the build nor known,
+    /// like ones set by build scripts with `cargo:rustc-cfg`.
+    pub(crate) fn unknown_flags(&self) -> Vec<String> {
+        self.unknown_flags.borrow().iter().cloned().collect()
+    }
+
+    fn eval(&self, predicate: &Predicate, platform: Platform) -> bool {
+        match predicate {
+            Predicate::All(predicates) => predicates
+                .iter()
+                .all(|predicate| self.eval(predicate, platform)),
+            Predicate::Any(predicates) => predicates
+                .iter()
+                .any(|predicate| self.eval(predicate, platform)),
+            Predicate::Not(predicate) => !self.eval(predicate, platform),
+            Predicate::Flag(name) if name == "unix" || name == "windows" => {
+                platform.target_family() == name
+            }
+            Predicate::Flag(name) if name == "desktop" => !platform.is_mobile(),
+            Predicate::Flag(name) if name == "mobile" => platform.is_mobile(),
+            Predicate::Flag(name) if self.options.contains_key(name) => true,
+            Predicate::Flag(name) => {
+                if !BUILTIN_FLAGS.contains(&name.as_str()) {
+                    self.unknown_flags.borrow_mut().insert(name.clone());
+                }
+                false
+            }
+            Predicate::KeyValue(key, value) if key == "target_os" => platform.target_os() == value,
+            Predicate::KeyValue(key, value) if key == "target_family" => {
+                platform.target_family() == value
+            }
+            Predicate::KeyValue(key, value) if key == "feature" => self
+                .features
+                .contains(&value.to_ascii_uppercase().replace('-', "_")),
+            Predicate::KeyValue(key, value) => self
+                .options
+                .get(key)
+                .is_some_and(|values| values.contains(value)),
+        }
+    }
+}
+
+/// The `#[cfg]` predicates among the attributes, like `feature = "sync"`.
+pub(crate) fn cfg_predicates(attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .filter_map(|attr| attr.parse_args::<Predicate>().ok())
+        .map(|predicate| predicate.to_string())
+        .collect()
+}
+
+/// A [configuration predicate](https://doc.rust-lang.org/reference/conditional-compilation.html).
+#[derive(Debug, Clone)]
+enum Predicate {
+    All(Vec<Predicate>),
+    Any(Vec<Predicate>),
+    Not(Box<Predicate>),
+    /// `unix`, `debug_assertions`, ...
+    Flag(String),
+    /// `feature = "sync"`, `target_os = "windows"`, ...
+    KeyValue(String, String),
+}
+
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let list = |predicates: &[Predicate]| {
+            predicates
+                .iter()
+                .map(Predicate::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            Predicate::All(predicates) => write!(f, "all({})", list(predicates)),
+            Predicate::Any(predicates) => write!(f, "any({})", list(predicates)),
+            Predicate::Not(predicate) => write!(f, "not({})", predicate),
+            Predicate::Flag(name) => write!(f, "{}", name),
+            Predicate::KeyValue(key, value) => write!(f, "{} = {:?}", key, value),
+        }
+    }
+}
+
+impl Parse for Predicate {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse::<Ident>()?.to_string();
+
+        if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            let value = input.parse::<LitStr>()?.value();
+            return Ok(Predicate::KeyValue(name, value));
+        }
+        if !input.peek(syn::token::Paren) {
+            return Ok(Predicate::Flag(name));
+        }
+
+        let content;
+        syn::parenthesized!(content in input);
+        let mut predicates = Punctuated::<Predicate, Token![,]>::parse_terminated(&content)?
+            .into_iter()
+            .collect::<Vec<_>>();
+        match name.as_str() {
+            "all" => Ok(Predicate::All(predicates)),
+            "any" => Ok(Predicate::Any(predicates)),
+            "not" if predicates.len() == 1 => Ok(Predicate::Not(Box::new(predicates.remove(0)))),
+            _ => Err(input.error(format!("unknown configuration predicate `{}`", name))),
+        }
+    }
+}
+
+/// The arguments of `#[cfg_attr(predicate, attrs...)]`.
+struct CfgAttr {
+    predicate: Predicate,
+    attrs: Vec<Meta>,
+}
+
+impl CfgAttr {
+    /// The attributes, as if they were written instead of the `cfg_attr`.
+    fn into_attrs(self, cfg_attr: &Attribute) -> Vec<Attribute> {
+        self.attrs
+            .into_iter()
+            .map(|meta| Attribute {
+                pound_token: cfg_attr.pound_token,
+                style: AttrStyle::Outer,
+                bracket_token: cfg_attr.bracket_token,
+                meta,
+            })
+            .collect()
+    }
+}
+
+impl Parse for CfgAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let predicate = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let attrs = Punctuated::<Meta, Token![,]>::parse_terminated(input)?
+            .into_iter()
+            .collect();
+        Ok(CfgAttr { predicate, attrs })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg() -> Cfg {
+        let mut cfg = Cfg::default();
+        cfg.features.insert("SYNC_DB".to_string());
+        cfg.options
+            .insert("target_pointer_width".to_string(), vec!["64".to_string()]);
+        cfg.options
+            .insert("debug_assertions".to_string(), Vec::new());
+        cfg
+    }
+
+    /// The platforms the predicate holds on.
+    fn eval(cfg: &Cfg, predicate: &str) -> Vec<Platform> {
+        let predicate = syn::parse_str::<Predicate>(predicate).unwrap();
+        Platform::ALL
+            .into_iter()
+            .filter(|&platform| cfg.eval(&predicate, platform))
+            .collect()
+    }
+
+    #[test]
+    fn operating_system() {
+        let cfg = cfg();
+        assert_eq!(eval(&cfg, r#"target_os = "macos""#), [Platform::Macos]);
+        assert_eq!(
+            eval(&cfg, r#"target_family = "windows""#),
+            [Platform::Windows]
+        );
+        assert_eq!(eval(&cfg, "windows"), [Platform::Windows]);
+        assert_eq!(
+            eval(&cfg, "unix"),
+            [
+                Platform::Linux,
+                Platform::Macos,
+                Platform::Ios,
+                Platform::Android
+            ]
+        );
+        assert_eq!(
+            eval(&cfg, "desktop"),
+            [Platform::Linux, Platform::Macos, Platform::Windows]
+        );
+        assert_eq!(eval(&cfg, "mobile"), [Platform::Ios, Platform::Android]);
+    }
+
+    #[test]
+    fn environment() {
+        let cfg = cfg();
+        assert_eq!(eval(&cfg, r#"feature = "sync-db""#), Platform::ALL);
+        assert_eq!(eval(&cfg, r#"feature = "sync""#), []);
+        assert_eq!(eval(&cfg, r#"target_pointer_width = "64""#), Platform::ALL);
+        assert_eq!(eval(&cfg, r#"target_pointer_width = "32""#), []);
+        assert_eq!(eval(&cfg, "debug_assertions"), Platform::ALL);
+        assert_eq!(eval(&cfg, "test"), []);
+        assert!(cfg.unknown_flags().is_empty());
+    }
+
+    #[test]
+    fn combinators() {
+        let cfg = cfg();
+        assert_eq!(eval(&cfg, "not(unix)"), [Platform::Windows]);
+        assert_eq!(
+            eval(&cfg, r#"all(unix, not(target_os = "linux"), desktop)"#),
+            [Platform::Macos]
+        );
+        assert_eq!(
+            eval(&cfg, r#"any(target_os = "ios", windows)"#),
+            [Platform::Windows, Platform::Ios]
+        );
+        assert_eq!(eval(&cfg, r#"all(windows, feature = "sync")"#), []);
+        assert_eq!(eval(&cfg, "all()"), Platform::ALL);
+        assert_eq!(eval(&cfg, "any()"), []);
+    }
+
+    #[test]
+    fn unknown_flags() {
+        let cfg = cfg();
+        assert_eq!(eval(&cfg, "tokio_unstable"), []);
+        assert_eq!(eval(&cfg, "not(tokio_unstable)"), Platform::ALL);
+        assert_eq!(cfg.unknown_flags(), ["tokio_unstable"]);
+    }
+
+    #[test]
+    fn display() {
+        let predicate =
+            syn::parse_str::<Predicate>(r#"all(unix,not(feature="sync"),any(test, desktop))"#)
+                .unwrap();
+        assert_eq!(
+            predicate.to_string(),
+            r#"all(unix, not(feature = "sync"), any(test, desktop))"#
+        );
+    }
+
+    /// The values of the `#[path]` attributes.
+    fn paths(attrs: &[Attribute]) -> Vec<String> {
+        attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("path"))
+            .filter_map(|attr| match &attr.meta {
+                Meta::NameValue(meta) => match &meta.value {
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(path),
+                        ..
+                    }) => Some(path.value()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn attributes() {
+        let cfg = cfg();
+        let module = syn::parse_str::<syn::ItemMod>(
+            r#"
+            #[cfg_attr(windows, path = "windows.rs")]
+            #[cfg_attr(debug_assertions, allow(dead_code), cfg_attr(unix, path = "unix.rs"))]
+            #[cfg_attr(test, path = "test.rs")]
+            mod platform;
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            paths(&cfg.expand_attrs_on(&module.attrs, Platform::Windows)),
+            ["windows.rs"]
+        );
+        assert_eq!(
+            paths(&cfg.expand_attrs_on(&module.attrs, Platform::Linux)),
+            ["unix.rs"]
+        );
+        assert_eq!(
+            paths(&cfg.expand_attrs(&module.attrs)),
+            ["windows.rs", "unix.rs"]
+        );
+        let platform_attrs = cfg.platform_attrs(&module.attrs);
+        assert_eq!(platform_attrs.len(), 2);
+        assert_eq!(paths(&platform_attrs), ["windows.rs", "unix.rs"]);
+
+        let module = syn::parse_str::<syn::ItemMod>(
+            r#"#[cfg_attr(windows, cfg(feature = "sync"))] mod sync;"#,
+        )
+        .unwrap();
+        assert_eq!(
+            cfg.platforms(&module.attrs),
+            [
+                Platform::Linux,
+                Platform::Macos,
+                Platform::Ios,
+                Platform::Android
+            ]
+        );
+    }
+}
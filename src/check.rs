@@ -0,0 +1,53 @@
+use std::path::{Path, PathBuf};
+
+use similar::TextDiff;
+
+use crate::emit::EmittedFile;
+
+/// Enables the check mode, like [`Builder::check`](crate::Builder::check).
+pub(crate) const CHECK_VAR: &str = "TAURI_NAMED_INVOKE_CHECK";
+
+/// Whether the check mode is enabled with the environment variable, set to anything but `0`.
+pub(crate) fn check_var() -> bool {
+    println!("cargo:rerun-if-env-changed={CHECK_VAR}");
+    std::env::var(CHECK_VAR).is_ok_and(|value| !value.is_empty() && value != "0")
+}
+
+/// Compares the generated files with the files under `root`, and checks that the `obsolete` files
+/// that would be removed are gone, returning a unified diff of those that differ,
+/// `None` if they are all up to date.
+pub(crate) fn stale_files<'a>(
+    root: &Path,
+    files: impl IntoIterator<Item = &'a EmittedFile>,
+    obsolete: &[PathBuf],
+) -> Option<String> {
+    let mut diff = String::new();
+    for file in files {
+        let path = root.join(&file.path);
+        // A file that doesn't exist yet shows up as entirely added.
+        let current = std::fs::read_to_string(&path).unwrap_or_default();
+        if current == file.content {
+            continue;
+        }
+
+        let name = file.path.display().to_string();
+        diff.push_str(
+            &TextDiff::from_lines(&current, &file.content)
+                .unified_diff()
+                .header(&format!("a/{name}"), &format!("b/{name}"))
+                .to_string(),
+        );
+    }
+    for path in obsolete {
+        let Ok(current) = std::fs::read_to_string(root.join(path)) else {
+            continue;
+        };
+        diff.push_str(
+            &TextDiff::from_lines(current.as_str(), "")
+                .unified_diff()
+                .header(&format!("a/{}", path.display()), "/dev/null")
+                .to_string(),
+        );
+    }
+    (!diff.is_empty()).then_some(diff)
+}
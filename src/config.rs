@@ -0,0 +1,124 @@
+use std::path::PathBuf;
+
+use crate::TauriVersion;
+
+/// The indentation of the generated file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indent {
+    Spaces(usize),
+    Tab,
+}
+
+impl Default for Indent {
+    fn default() -> Self {
+        Indent::Spaces(4)
+    }
+}
+
+impl Indent {
+    /// One level of indentation.
+    pub(crate) fn unit(self) -> String {
+        match self {
+            Indent::Spaces(count) => " ".repeat(count),
+            Indent::Tab => "\t".to_string(),
+        }
+    }
+}
+
+/// How the files to scan for commands are found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Discovery {
+    /// All `.rs` files under the scan root, see [`Builder::scan_root`](crate::Builder::scan_root).
+    /// The `#[cfg]` attributes of `mod` declarations apply to the files of the modules,
+    /// found by following the declarations from the roots of the crate targets.
+    #[default]
+    Files,
+    /// The files of the modules of the crate, found by following the `mod` declarations
+    /// from the roots of the library and binary targets. Commands from examples, tests,
+    /// benches and files that aren't compiled are left out.
+    ModuleTree,
+}
+
+/// The order of the commands in the generated files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    /// Alphabetical order of the command names.
+    #[default]
+    Name,
+    /// The order of the definitions: by file path, then by line.
+    Location,
+}
+
+/// The settings of the generation, usually assembled with [`Builder`](crate::Builder).
+/// Start from [`Config::default`] to set the fields directly, new ones may be added.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Config {
+    /// The directory of the generated file, relative to the crate root.
+    pub out_dir: PathBuf,
+    pub file_name: String,
+    pub discovery: Discovery,
+    /// The directory searched for commands, relative to the crate root.
+    pub scan_root: PathBuf,
+    /// Globs of the files to scan, all `.rs` files if empty.
+    pub include: Vec<String>,
+    /// Globs of the files and directories to skip.
+    pub exclude: Vec<String>,
+    /// Skip `target`, `node_modules` and `dist` directories.
+    pub default_excludes: bool,
+    /// Overrides the module whose `invoke` is declared.
+    pub module: Option<String>,
+    /// Detected from the `tauri` dependency if not set.
+    pub tauri_version: Option<TauriVersion>,
+    pub extractors: Vec<String>,
+    /// Leave out the commands that aren't registered with `generate_handler!`.
+    pub registered_only: bool,
+    pub indent: Indent,
+    /// The crate is a Tauri plugin, its commands are invoked as `plugin:<name>|<command>`.
+    pub plugin: bool,
+    /// Read from `tauri::plugin::Builder::new` if not set.
+    pub plugin_name: Option<String>,
+    /// Write the permission files of the commands.
+    pub permissions: bool,
+    /// Generate the declaration file augmenting `invoke`.
+    pub declarations: bool,
+    /// The path of the module of functions calling the commands, relative to the crate root.
+    /// `guest-js/bindings.ts` for plugins if not set.
+    pub bindings: Option<PathBuf>,
+    /// The path of the JSON manifest of the commands, relative to the crate root.
+    pub manifest: Option<PathBuf>,
+    /// Compare the generated files with the ones on disk instead of writing them.
+    pub check: bool,
+    /// Fail the build on warnings.
+    pub strict: bool,
+    /// The order of the commands.
+    pub sort_by: SortKey,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            out_dir: PathBuf::new(),
+            file_name: "invoke.d.ts".to_string(),
+            discovery: Discovery::default(),
+            scan_root: PathBuf::new(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            default_excludes: true,
+            module: None,
+            tauri_version: None,
+            extractors: Vec::new(),
+            registered_only: true,
+            indent: Indent::default(),
+            plugin: false,
+            plugin_name: None,
+            permissions: false,
+            declarations: true,
+            bindings: None,
+            manifest: None,
+            check: false,
+            strict: false,
+            sort_by: SortKey::default(),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::{fmt, path::PathBuf};
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The files are generated, but may be less precise, e.g. with `any` for a type.
+    /// Fails the build in strict mode, see [`Builder::strict`](crate::Builder::strict).
+    Warning,
+    /// The files can't be generated correctly, the build fails.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A problem found while scanning the sources, like a command parameter
+/// whose type can't be described.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// The file the problem is in, relative to the crate root.
+    pub file: Option<PathBuf>,
+    /// The line in the file, starting from 1.
+    pub line: Option<usize>,
+}
+
+impl Diagnostic {
+    pub(crate) fn warning(message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            message: message.into(),
+            file: None,
+            line: None,
+        }
+    }
+
+    pub(crate) fn error(message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            ..Diagnostic::warning(message)
+        }
+    }
+
+    /// Points the diagnostic to a file.
+    pub(crate) fn in_file(mut self, file: impl Into<PathBuf>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    /// Points the diagnostic to a line of a file.
+    pub(crate) fn at(self, file: impl Into<PathBuf>, line: usize) -> Self {
+        Diagnostic {
+            line: Some(line),
+            ..self.in_file(file)
+        }
+    }
+
+    /// Prints the diagnostic for cargo, which shows it after the build script has run.
+    pub(crate) fn print(&self) {
+        match self.severity {
+            Severity::Warning => println!("cargo:warning={}", self),
+            Severity::Error => println!("cargo:warning=error: {}", self),
+        }
+    }
+}
+
+/// `src/commands.rs:42: message`, or just the message if it isn't about a file.
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => write!(f, "{}:{}: {}", file.display(), line, self.message),
+            (Some(file), None) => write!(f, "{}: {}", file.display(), self.message),
+            (None, _) => write!(f, "{}", self.message),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::{error::Error, path::PathBuf};
+
+use crate::{
+    config::Config,
+    manifest::get_manifest,
+    parse::Model,
+    render::{get_bindings, get_content},
+};
+
+/// Generates files from the commands found in the sources.
+///
+/// Besides the built-in [`Declarations`], [`Bindings`] and [`JsonManifest`], emitters
+/// can be registered with [`Builder::emitter`](crate::Builder::emitter), e.g. to generate
+/// bindings for another language:
+///
+/// ```rust
+/// use tauri_named_invoke::{Config, EmittedFile, Emitter, Model};
+///
+/// struct CommandList;
+///
+/// impl Emitter for CommandList {
+///     fn emit(
+///         &self,
+///         model: &Model,
+///         _config: &Config,
+///     ) -> Result<Vec<EmittedFile>, Box<dyn std::error::Error + Send + Sync>> {
+///         let names = model
+///             .commands
+///             .iter()
+///             .map(|command| format!("'{}'", command.name))
+///             .collect::<Vec<_>>();
+///         Ok(vec![EmittedFile {
+///             path: "ui/commands.js".into(),
+///             content: format!("export const commands = [{}];\n", names.join(", ")),
+///         }])
+///     }
+/// }
+/// ```
+pub trait Emitter {
+    /// Renders the files, whose paths are relative to the crate root.
+    fn emit(
+        &self,
+        model: &Model,
+        config: &Config,
+    ) -> Result<Vec<EmittedFile>, Box<dyn Error + Send + Sync>>;
+}
+
+/// A file generated by an [`Emitter`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmittedFile {
+    /// Relative to the crate root.
+    pub path: PathBuf,
+    pub content: String,
+}
+
+/// The declaration file augmenting `invoke`, written to the `out_dir` and `file_name` of the config.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Declarations;
+
+impl Emitter for Declarations {
+    fn emit(
+        &self,
+        model: &Model,
+        config: &Config,
+    ) -> Result<Vec<EmittedFile>, Box<dyn Error + Send + Sync>> {
+        Ok(vec![EmittedFile {
+            path: config.out_dir.join(&config.file_name),
+            content: get_content(model, config),
+        }])
+    }
+}
+
+/// The TypeScript module of functions calling the commands, see [`Builder::bindings`](crate::Builder::bindings).
+#[derive(Debug, Clone)]
+pub struct Bindings {
+    pub path: PathBuf,
+}
+
+impl Emitter for Bindings {
+    fn emit(
+        &self,
+        model: &Model,
+        config: &Config,
+    ) -> Result<Vec<EmittedFile>, Box<dyn Error + Send + Sync>> {
+        Ok(vec![EmittedFile {
+            path: self.path.clone(),
+            content: get_bindings(model, config),
+        }])
+    }
+}
+
+/// The JSON manifest of the commands, see [`Builder::manifest`](crate::Builder::manifest).
+#[derive(Debug, Clone)]
+pub struct JsonManifest {
+    pub path: PathBuf,
+}
+
+impl Emitter for JsonManifest {
+    fn emit(
+        &self,
+        model: &Model,
+        _config: &Config,
+    ) -> Result<Vec<EmittedFile>, Box<dyn Error + Send + Sync>> {
+        Ok(vec![EmittedFile {
+            path: self.path.clone(),
+            content: get_manifest(model),
+        }])
+    }
+}
@@ -0,0 +1,80 @@
+use std::{fmt, io, path::PathBuf};
+
+use crate::diagnostics::Diagnostic;
+
+/// The ways the generation can fail.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A file couldn't be read or written.
+    Io { path: PathBuf, source: io::Error },
+    /// A file couldn't be parsed, e.g. a malformed `Cargo.toml`.
+    Parse {
+        path: PathBuf,
+        /// Starting from 1.
+        line: usize,
+        message: String,
+    },
+    /// The settings are invalid, like a malformed glob, or the environment
+    /// isn't the one of a build script.
+    Config { message: String },
+    /// The generated files differ from the ones on disk, in check mode.
+    Stale {
+        /// A unified diff from the files on disk to the generated ones.
+        diff: String,
+    },
+    /// An [`Emitter`](crate::Emitter) failed.
+    Emit(Box<dyn std::error::Error + Send + Sync>),
+    /// Problems were found in the sources: errors, or warnings in strict mode.
+    Diagnostics(Vec<Diagnostic>),
+}
+
+impl Error {
+    pub(crate) fn io(path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> Error {
+        let path = path.into();
+        move |source| Error::Io { path, source }
+    }
+
+    pub(crate) fn config(message: impl Into<String>) -> Error {
+        Error::Config {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            Error::Parse {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", path.display(), line, message),
+            Error::Config { message } => write!(f, "{}", message),
+            Error::Stale { diff } => write!(
+                f,
+                "The generated files are out of date, build without the check mode to update them:\n{}",
+                diff
+            ),
+            Error::Emit(err) => write!(f, "{}", err),
+            Error::Diagnostics(diagnostics) => {
+                write!(f, "Problems were found in the sources:")?;
+                for diagnostic in diagnostics {
+                    write!(f, "\n{}: {}", diagnostic.severity, diagnostic)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Emit(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
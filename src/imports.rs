@@ -0,0 +1,126 @@
+use std::collections::{HashMap, HashSet};
+
+use syn::{ext::IdentExt, Item, UseTree};
+
+/// The names brought into scope by the `use` declarations of a file,
+/// used to resolve the paths written in it.
+///
+/// Scoping is approximated: the declarations of all modules of the file are merged.
+#[derive(Debug, Default)]
+pub(crate) struct Imports {
+    /// `use tauri::State as S;` maps `S` to `tauri::State`.
+    aliases: HashMap<String, Vec<String>>,
+    /// Types defined in the file, which shadow glob imports.
+    local_types: HashSet<String>,
+    /// The paths of glob imports: `use tauri::ipc::*;` is `tauri::ipc`.
+    globs: Vec<Vec<String>>,
+}
+
+impl Imports {
+    pub(crate) fn collect(items: &[Item]) -> Self {
+        let mut imports = Imports::default();
+        imports.collect_items(items);
+        imports
+    }
+
+    fn collect_items(&mut self, items: &[Item]) {
+        for item in items {
+            match item {
+                Item::Use(item) => self.collect_tree(&item.tree, Vec::new()),
+                Item::Struct(item) => {
+                    self.local_types.insert(item.ident.unraw().to_string());
+                }
+                Item::Enum(item) => {
+                    self.local_types.insert(item.ident.unraw().to_string());
+                }
+                Item::Type(item) => {
+                    self.local_types.insert(item.ident.unraw().to_string());
+                }
+                Item::Mod(module) => {
+                    if let Some((_, items)) = &module.content {
+                        self.collect_items(items);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn collect_tree(&mut self, tree: &UseTree, mut prefix: Vec<String>) {
+        match tree {
+            UseTree::Path(path) => {
+                prefix.push(path.ident.unraw().to_string());
+                self.collect_tree(&path.tree, prefix);
+            }
+            UseTree::Name(name) if name.ident == "self" => {
+                if let Some(last) = prefix.last() {
+                    self.aliases.insert(last.clone(), prefix);
+                }
+            }
+            UseTree::Name(name) => {
+                let name = name.ident.unraw().to_string();
+                prefix.push(name.clone());
+                self.aliases.insert(name, prefix);
+            }
+            UseTree::Rename(rename) => {
+                if rename.ident != "self" {
+                    prefix.push(rename.ident.unraw().to_string());
+                }
+                self.aliases
+                    .insert(rename.rename.unraw().to_string(), prefix);
+            }
+            UseTree::Glob(_) => self.globs.push(prefix),
+            UseTree::Group(group) => {
+                for tree in &group.items {
+                    self.collect_tree(tree, prefix.clone());
+                }
+            }
+        }
+    }
+
+    /// The names of the types defined in the file.
+    pub(crate) fn local_types(&self) -> &HashSet<String> {
+        &self.local_types
+    }
+
+    /// Whether a glob import is in scope, like `use super::*;`.
+    pub(crate) fn has_globs(&self) -> bool {
+        !self.globs.is_empty()
+    }
+
+    /// Whether a glob import from the crate is in scope, like `use tauri::*;`.
+    pub(crate) fn has_glob_from(&self, krate: &str) -> bool {
+        self.globs
+            .iter()
+            .any(|glob| glob.first().is_some_and(|first| first == krate))
+    }
+
+    /// Whether `path` refers to an item of the crate, like `Channel` imported from `tauri::ipc`.
+    /// A name that isn't imported explicitly is assumed to come from a glob import of the crate.
+    pub(crate) fn is_from(&self, path: &syn::Path, krate: &str) -> bool {
+        match self.resolve(path) {
+            Some(resolved) => resolved.first().is_some_and(|first| first == krate),
+            None => self.has_glob_from(krate),
+        }
+    }
+
+    /// Resolves the first segment of `path` through the imports.
+    ///
+    /// Returns `None` if the path is a single name that isn't imported explicitly
+    /// nor defined in the file, i.e. it may come from a glob import or the prelude.
+    pub(crate) fn resolve(&self, path: &syn::Path) -> Option<Vec<String>> {
+        let mut segments = path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.unraw().to_string());
+        let first = segments.next()?;
+
+        let mut resolved = match self.aliases.get(&first) {
+            Some(target) => target.clone(),
+            None if path.segments.len() == 1 && !self.local_types.contains(&first) => return None,
+            None => vec![first],
+        };
+        resolved.extend(segments);
+        Some(resolved)
+    }
+}
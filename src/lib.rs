@@ -6,7 +6,7 @@
 //! 
 //! **main.rs:**
 //! 
-//! ```rust
+//! ```rust,ignore
 //! fn main() {
 //!     tauri::Builder::default()
 //!         .invoke_handler(generate_handler![get_weather, get_config])
@@ -15,7 +15,7 @@
 //! }
 //! 
 //! #[tauri::command]
-//! fn get_weather() -> String {
+//! fn get_weather(city: String) -> String {
 //!     "sunny".to_string()
 //! }
 //! // or
@@ -28,7 +28,7 @@
 //! 
 //! **build.rs:**
 //! 
-//! ```rust
+//! ```rust,ignore
 //! fn main() {
 //!     tauri_named_invoke::build("ui").unwrap();
 //!     tauri_build::build();
@@ -49,22 +49,151 @@
 //! The generated file will contain:
 //! 
 //! ```typescript
-//! import * as tauri from '@tauri-apps/api/tauri';
-//! declare module '@tauri-apps/api' {
-//!     type Commands = 
+//! import type { InvokeArgs, InvokeOptions } from '@tauri-apps/api/core';
+//! declare module '@tauri-apps/api/core' {
+//!     type Commands =
 //!           'get_weather'
 //!         | 'get_config';
-//!     function invoke<T>(cmd: Commands, args?: InvokeArgs): Promise<T>;
+//!     interface Args extends Record<Commands, InvokeArgs> {
+//!         'get_weather': { city: string };
+//!         'get_config': Record<string, never>;
+//!     }
+//!     interface Returns extends Record<Commands, unknown> {
+//!         'get_weather': string;
+//!         'get_config': string;
+//!     }
+//!     type InvokeParams<C extends Commands> = {} extends Args[C]
+//!         ? [args?: Args[C], options?: InvokeOptions]
+//!         : [args: Args[C], options?: InvokeOptions];
+//!     function invoke<C extends Commands>(cmd: C, ...params: InvokeParams<C>): Promise<Returns[C]>;
 //! }
 //! ```
 //! 
+//! Each command gets an entry in `Args` with its parameters, named in camelCase like Tauri expects them
+//! (or in snake_case for commands with `#[tauri::command(rename_all = "snake_case")]`),
+//! so `invoke('get_weather', { city })` is checked by the compiler. The result type comes from `Returns`:
+//! `Result<T, E>` resolves with `T`, `()` with `void`.
+//! Parameters that Tauri injects (`State`, `AppHandle`, `Window`, `Webview`, `Request`, ...) are left out of `Args`,
+//! for your own extractor types use [`Builder::extractor`].
+//! 
+//! Structs and enums deriving `Serialize` or `Deserialize` that commands take or return are declared too,
+//! following serde's `rename`, `rename_all`, `tag`, `content`, `untagged`, `flatten`, `skip` and `default` attributes:
+//! 
+//! ```typescript
+//! export interface Weather {
+//!     city: string;
+//!     temperature: number;
+//! }
+//! ```
+//! 
+//! A `tauri::ipc::Channel<T>` parameter is declared as the `Channel<T>` of the core module, and `serde_json::Value` as `any`.
+//! 
+//! The declarations target the Tauri version of the `tauri` dependency found in `Cargo.toml` or `Cargo.lock`:
+//! for Tauri 1 the `@tauri-apps/api/tauri` module is augmented, for Tauri 2 - `@tauri-apps/api/core`.
+//! To generate declarations for a specific version, use [`Builder::tauri_version`].
+//! 
+//! # Configuration
+//! 
+//! Commands are searched for in the `.rs` files of the crate. `target`, `node_modules` and `dist` directories
+//! and paths listed in `.gitignore` and `.ignore` files are skipped, narrow the search down further with [`Builder::include`] and [`Builder::exclude`].
+//! To scan only the modules that are compiled into the crate, following `mod` declarations from `src/main.rs`,
+//! `src/lib.rs` and other targets of `Cargo.toml`, use [`Discovery::ModuleTree`].
+//! Only the commands registered in `generate_handler!` are declared, with a warning for each command that isn't
+//! registered and each registered command that isn't found. To declare all commands, use [`Builder::registered_only`].
+//! `#[cfg]` and `#[cfg_attr]` attributes are evaluated against the features and the target of the build,
+//! so commands, parameters and fields that aren't compiled are left out.
+//! 
+//! Commands gated on the operating system, like `#[cfg(target_os = "windows")]`, `#[cfg(unix)]` or `#[cfg(desktop)]`, are declared whatever
+//! the target of the build. They are marked with a comment in `Args` and listed in a union per platform, such as `WindowsCommands`
+//! or `MacosCommands`, to guard the calls on the frontend. Definitions of a command for different platforms are merged into one entry.
+//! The `cfg_attr` attributes on the operating system are evaluated for each platform as well, like `#[cfg_attr(target_os = "macos", tauri::command)]` or `#[cfg_attr(windows, path = "windows.rs")]`.
+//! Any other command defined twice in a target, e.g. in two modules, fails the build, while the definitions outside the module tree, like in an example, never replace those of the crate. The commands are sorted by name, or by their location with [`Builder::sort_by`].
+//! 
+//! Instead of augmenting `invoke`, [`Builder::bindings`] generates a TypeScript module with a typed function per command,
+//! like `getWeather(args: GetWeatherArgs): Promise<string>`, which doesn't depend on how `@tauri-apps/api` is resolved.
+//! The declaration file can then be turned off with [`Builder::declarations`].
+//! 
+//! In a Tauri plugin, enable [`Builder::plugin`] to declare the commands as `plugin:<name>|<command>`, the name being read
+//! from `tauri::plugin::Builder::new("<name>")` or set with [`Builder::plugin_name`]. A module of typed functions calling the commands
+//! is generated in `guest-js/bindings.ts` as well, ready to be exported from the JavaScript package of the plugin.
+//! 
+//! With [`Builder::permissions`], the `allow-<command>` and `deny-<command>` permissions that Tauri 2 requires for every command
+//! are written to `permissions/autogenerated`, along with a default permission set allowing all the commands.
+//! 
+//! For other tools, [`Builder::manifest`] writes a versioned JSON manifest listing each command with its source file and line,
+//! doc comment, parameters, return type and `cfg` gates, along with the types they use.
+//! 
+//! When the generated files are committed, run the build in CI with `TAURI_NAMED_INVOKE_CHECK=1` or [`Builder::check`]:
+//! instead of writing the files, the build fails with a diff if they are out of date.
+//! 
+//! Problems found in the sources are reported as cargo warnings pointing to the file and line, such as a command parameter
+//! typed as `any` because its type is generic, an `impl Trait` or isn't defined in the scanned sources.
+//! With [`Builder::strict`], the build fails on warnings.
+//! 
+//! [`build`] covers the common case. Other settings are available through the [`Builder`]:
+//! 
+//! ```rust,ignore
+//! fn main() {
+//!     tauri_named_invoke::Builder::new()
+//!         .out_dir("ui")
+//!         .file_name("commands.d.ts")
+//!         .indent(tauri_named_invoke::Indent::Tab)
+//!         .build()
+//!         .unwrap();
+//!     tauri_build::build();
+//! }
+//! ```
+//! 
+//! To filter or change the commands before they are declared, or to generate something else from them,
+//! [`scan`] returns the [`Model`] of the commands and the types they use, and [`render`] renders the declaration file:
+//! 
+//! ```rust,ignore
+//! fn main() {
+//!     let builder = tauri_named_invoke::Builder::new();
+//!     let mut model = tauri_named_invoke::scan(builder.config()).unwrap();
+//!     model.commands.retain(|command| !command.name.starts_with("debug_"));
+//!     let content = tauri_named_invoke::render(&model, builder.config());
+//!     std::fs::write("ui/invoke.d.ts", content).unwrap();
+//!     tauri_build::build();
+//! }
+//! ```
+//! 
+//! Files for other languages or tools can be generated along with the declarations by implementing [`Emitter`]
+//! and registering it with [`Builder::emitter`].
+//! 
 //! [`invoke`]: https://tauri.app/v1/api/js/tauri/#invoke
 //! [commands]: https://docs.rs/tauri/1.6.1/tauri/command/index.html
 
-use std::{env, path::Path};
+#![allow(clippy::needless_doctest_main)]
+
+mod builder;
+mod cfg;
+mod check;
+mod config;
+mod diagnostics;
+mod emit;
+mod error;
+mod imports;
+mod manifest;
+mod parse;
+mod permissions;
+mod render;
+mod typedef;
+mod types;
+mod version;
+mod walk;
+mod write;
 
-use glob::glob;
-use regex::Regex;
+pub use builder::Builder;
+pub use cfg::Platform;
+pub use config::{Config, Discovery, Indent, SortKey};
+pub use diagnostics::{Diagnostic, Severity};
+pub use emit::{Bindings, Declarations, EmittedFile, Emitter, JsonManifest};
+pub use error::Error;
+pub use parse::{CommandInfo, Model, ParamInfo};
+pub use typedef::{Field, Fields, Shape, Tagging, TypeDef, Variant};
+pub use types::TypeRef;
+pub use version::TauriVersion;
 
 /// Generates an `invoke.d.ts` file declaring [`invoke`] function values composed 
 /// of function names labeled with the [`tauri::command`] attribute.
@@ -73,7 +202,7 @@ use regex::Regex;
 /// 
 /// # Example
 /// 
-/// ```rust
+/// ```rust,no_run
 /// fn main() {
 ///     tauri_named_invoke::build("ui").unwrap();
 /// }
@@ -92,43 +221,23 @@ use regex::Regex;
 /// 
 /// [`invoke`]: https://tauri.app/v1/api/js/tauri/#invoke
 /// [`tauri::command`]: https://docs.rs/tauri/1.6.1/tauri/command/index.html
-pub fn build(path: impl AsRef<std::path::Path>) -> Result<(), Box<dyn std::error::Error>> {
-    let typed_file = Path::new(env::var("CARGO_MANIFEST_DIR")?.as_str())
-        .join(path)
-        .join("invoke.d.ts");
-    let fn_names = parse_functions();
-    std::fs::write(typed_file, get_content(fn_names))?;
-    Ok(())
+pub fn build(path: impl AsRef<std::path::Path>) -> Result<(), Error> {
+    Builder::new().out_dir(path.as_ref()).build()
 }
 
-fn parse_functions() -> Vec<String> {
-    let mut names = Vec::new();
-
-    let rx = Regex::new(r"(?m)\#\[(?:tauri::)?command][\s\w]*fn\s+([\w\d_-]+)").unwrap();
-    for file in glob("**/*.rs").unwrap() {
-        let file = file.unwrap();
-        println!("cargo:rerun-if-changed={}", file.display());
-        let content = std::fs::read_to_string(file).unwrap();
-        for cap in rx.captures_iter(&content) {
-            names.push(cap[1].to_string());
-        }
-    }
-
-    names
+/// Finds the commands and the types they use in the sources of the crate being built,
+/// without generating anything.
+///
+/// Use it with [`render`] to change the commands before they are declared.
+/// Only `cargo:rerun-if-changed` is printed for each scanned file, the problems found
+/// are listed in [`Model::diagnostics`] instead of being printed as warnings.
+pub fn scan(config: &Config) -> Result<Model, Error> {
+    parse::parse_functions(&builder::manifest_dir()?, config)
 }
 
-fn get_content(names: Vec<String>) -> String {
-    let names = names
-        .iter()
-        .map(|f| format!("'{}'", f))
-        .collect::<Vec<_>>()
-        .join("\n\t\t| ");
-
-    format!(
-"import * as tauri from '@tauri-apps/api/tauri';
-declare module '@tauri-apps/api/tauri' {{
-    type Commands = 
-\t\t  {};
-    function invoke<T>(cmd: Commands, args?: InvokeArgs): Promise<T>;
-}}", names)
-}
\ No newline at end of file
+/// Renders the declaration file of the commands, as [`build`] writes it.
+///
+/// The config sets the indentation and the module to augment.
+pub fn render(model: &Model, config: &Config) -> String {
+    render::get_content(model, config)
+}
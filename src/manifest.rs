@@ -0,0 +1,82 @@
+use serde::Serialize;
+
+use crate::{
+    cfg::Platform,
+    parse::{CommandInfo, Model},
+    typedef::TypeDef,
+    types::TypeRef,
+};
+
+/// The version of the format of the manifest, increased on every breaking change.
+const FORMAT_VERSION: u32 = 1;
+
+/// The commands and the types they use, for the tools that can't read Rust.
+#[derive(Serialize)]
+struct Manifest<'a> {
+    version: u32,
+    /// The name of the plugin, in plugin mode.
+    plugin: Option<&'a str>,
+    commands: Vec<CommandEntry<'a>>,
+    types: &'a [TypeDef],
+}
+
+#[derive(Serialize)]
+struct CommandEntry<'a> {
+    name: &'a str,
+    /// Relative to the crate root, with `/` separators.
+    file: String,
+    line: usize,
+    docs: Option<&'a str>,
+    params: Vec<ParamEntry<'a>>,
+    returns: &'a TypeRef,
+    platforms: &'a [Platform],
+    cfg: &'a [String],
+}
+
+#[derive(Serialize)]
+struct ParamEntry<'a> {
+    name: &'a str,
+    #[serde(rename = "type")]
+    ty: &'a TypeRef,
+}
+
+/// Describes the commands in JSON.
+pub(crate) fn get_manifest(model: &Model) -> String {
+    let manifest = Manifest {
+        version: FORMAT_VERSION,
+        plugin: model.plugin.as_deref(),
+        commands: model.commands.iter().map(command_entry).collect(),
+        types: &model.types,
+    };
+    let mut json =
+        serde_json::to_string_pretty(&manifest).expect("the manifest is always serializable");
+    json.push('\n');
+    json
+}
+
+fn command_entry(command: &CommandInfo) -> CommandEntry<'_> {
+    let file = command
+        .file
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    CommandEntry {
+        name: &command.name,
+        file,
+        line: command.line,
+        docs: command.docs.as_deref(),
+        params: command
+            .params
+            .iter()
+            .map(|param| ParamEntry {
+                name: &param.name,
+                ty: &param.ty,
+            })
+            .collect(),
+        returns: &command.ret,
+        platforms: &command.platforms,
+        cfg: &command.cfg,
+    }
+}
@@ -0,0 +1,1482 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    path::{Path, PathBuf},
+};
+
+use heck::{ToLowerCamelCase, ToSnakeCase};
+use syn::{
+    ext::IdentExt,
+    punctuated::Punctuated,
+    visit::{self, Visit},
+    Attribute, Expr, ExprCall, ExprLit, FnArg, Item, ItemEnum, ItemFn, ItemStruct, Lit, Macro,
+    Meta, Pat, ReturnType, Token, Type,
+};
+
+use crate::{
+    cfg::{cfg_predicates, Cfg, Platform},
+    config::{Config, Discovery, SortKey},
+    diagnostics::Diagnostic,
+    error::Error,
+    imports::Imports,
+    typedef::{is_serde_attr, TypeDef},
+    types::{TypeRef, OPAQUE_TYPES},
+    walk::{crate_roots, module_file, normalize, source_files},
+    TauriVersion,
+};
+
+/// Types that Tauri passes to a command by itself, they never come from `args`.
+const INJECTED: &[&str] = &[
+    "State",
+    "AppHandle",
+    "Window",
+    "WebviewWindow",
+    "Webview",
+    "Request",
+    "CommandScope",
+    "GlobalScope",
+];
+
+/// A function marked as a Tauri command.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CommandInfo {
+    pub name: String,
+    pub params: Vec<ParamInfo>,
+    /// The type of the value the command resolves with, `Result` unwrapped.
+    pub ret: TypeRef,
+    /// The platforms the command is compiled for, all of them unless
+    /// it's gated with `#[cfg(target_os = "...")]` or alike.
+    pub platforms: Vec<Platform>,
+    /// The `#[cfg]` predicates of the command and of the modules it's in, like `feature = "sync"`.
+    pub cfg: Vec<String>,
+    /// The file the command is defined in, relative to the crate root.
+    pub file: PathBuf,
+    /// The line of the name of the command, starting from 1.
+    pub line: usize,
+    /// The doc comment of the command.
+    pub docs: Option<String>,
+}
+
+impl CommandInfo {
+    /// Whether the command is missing on some platforms.
+    pub fn is_platform_specific(&self) -> bool {
+        self.platforms.len() < Platform::ALL.len()
+    }
+}
+
+/// A command parameter, as it must be passed in the `args` object of `invoke`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ParamInfo {
+    /// The key of the argument, converted to camelCase like Tauri does,
+    /// unless the command has `rename_all = "snake_case"`.
+    pub name: String,
+    pub ty: TypeRef,
+}
+
+/// The commands and the types they use, found in the sources by [`scan`](crate::scan).
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct Model {
+    pub commands: Vec<CommandInfo>,
+    /// The structs and enums used by the commands.
+    pub types: Vec<TypeDef>,
+    /// The name of the plugin the commands belong to, in plugin mode.
+    pub plugin: Option<String>,
+    /// The version of Tauri the crate depends on, or the one set in the config.
+    pub tauri_version: TauriVersion,
+    /// The problems found in the sources, printed as warnings by [`Builder::build`](crate::Builder::build).
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Model {
+    /// Merges the definitions of a command for different platforms, like
+    /// `#[cfg(windows)] fn open()` and `#[cfg(not(windows))] fn open()`, into one.
+    /// Other definitions with the same name are left out, and reported as errors when
+    /// they are compiled together. `targets` maps the files of the module tree to their target.
+    fn merge_platforms(&mut self, targets: &HashMap<PathBuf, usize>) {
+        // The compilation unit of a command: a target of the crate for the files of
+        // its module tree, or the file itself for the others, like an example.
+        let unit = |command: &CommandInfo| {
+            targets
+                .get(&command.file)
+                .copied()
+                .ok_or(command.file.clone())
+        };
+        // The definitions of the module tree come first, so that the others never displace them.
+        self.commands
+            .sort_by_key(|command| targets.get(&command.file).copied().unwrap_or(usize::MAX));
+
+        let mut merged = Vec::<CommandInfo>::new();
+        for command in std::mem::take(&mut self.commands) {
+            let Some(other) = merged.iter_mut().find(|other| other.name == command.name) else {
+                merged.push(command);
+                continue;
+            };
+            // A command of another compilation unit, like an example, is a different one.
+            if unit(other) != unit(&command) {
+                continue;
+            }
+            let overlaps = other
+                .platforms
+                .iter()
+                .any(|platform| command.platforms.contains(platform));
+            if overlaps {
+                self.diagnostics.push(
+                    Diagnostic::error(format!(
+                        "Command `{}` is already defined at {}:{}",
+                        command.name,
+                        other.file.display(),
+                        other.line
+                    ))
+                    .at(&command.file, command.line),
+                );
+                continue;
+            }
+
+            if other.params != command.params || other.ret != command.ret {
+                self.diagnostics.push(
+                    Diagnostic::warning(format!(
+                        "Command `{}` has a different signature on {}, the one for {} is used",
+                        command.name,
+                        platform_list(&command.platforms),
+                        platform_list(&other.platforms)
+                    ))
+                    .at(&command.file, command.line),
+                );
+            }
+            other.platforms.extend(command.platforms);
+            other.platforms.sort();
+            if other.cfg != command.cfg {
+                other.cfg = vec![format!(
+                    "any({}, {})",
+                    all_predicates(&other.cfg),
+                    all_predicates(&command.cfg)
+                )];
+            }
+        }
+        self.commands = merged;
+    }
+
+    /// Orders the commands, so that the output doesn't depend on the order the files are scanned in.
+    fn sort_commands(&mut self, key: SortKey) {
+        match key {
+            SortKey::Name => self.commands.sort_by(|a, b| a.name.cmp(&b.name)),
+            SortKey::Location => self
+                .commands
+                .sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line))),
+        }
+    }
+
+    /// Reports the commands that aren't registered with `generate_handler!`
+    /// and the registered ones that weren't found, leaving out the former if `registered_only`.
+    fn check_registered(&mut self, registered: &[String], registered_only: bool) {
+        for name in registered {
+            if !self.commands.iter().any(|command| &command.name == name) {
+                self.diagnostics.push(Diagnostic::warning(format!(
+                    "Command `{}` is registered in `generate_handler!`, but its definition was not found",
+                    name
+                )));
+            }
+        }
+
+        let diagnostics = &mut self.diagnostics;
+        self.commands.retain(|command| {
+            let is_registered = registered.contains(&command.name);
+            if !is_registered {
+                diagnostics.push(
+                    Diagnostic::warning(format!(
+                        "Command `{}` is not registered in `generate_handler!`{}",
+                        command.name,
+                        if registered_only {
+                            ", it is left out"
+                        } else {
+                            ""
+                        }
+                    ))
+                    .at(&command.file, command.line),
+                );
+            }
+            is_registered || !registered_only
+        });
+    }
+
+    /// Keeps only the types used by the commands, and replaces references
+    /// to types that weren't found with `any`, with a warning for each command using them.
+    fn resolve_types(&mut self) {
+        let mut defined = HashSet::new();
+        let diagnostics = &mut self.diagnostics;
+        self.types.retain(|def| {
+            let first = defined.insert(def.name.clone());
+            if !first {
+                diagnostics.push(Diagnostic::warning(format!(
+                    "Type `{}` is defined more than once, the first definition is used",
+                    def.name
+                )));
+            }
+            first
+        });
+
+        for command in &mut self.commands {
+            let positions = command
+                .params
+                .iter_mut()
+                .map(|param| (format!("Parameter `{}`", param.name), &mut param.ty))
+                .chain([("The return type".to_string(), &mut command.ret)]);
+            for (position, ty) in positions {
+                let mut unknown = Vec::new();
+                ty.visit_names(&mut |name| {
+                    let is_unknown = !defined.contains(name) && !OPAQUE_TYPES.contains(&name);
+                    if is_unknown && !unknown.iter().any(|known| known == name) {
+                        unknown.push(name.to_string());
+                    }
+                });
+                for name in unknown {
+                    diagnostics.push(
+                        Diagnostic::warning(format!(
+                            "{} of command `{}` uses `{}`, which isn't a serializable type defined in the scanned sources, it's typed as `any`",
+                            position, command.name, name
+                        ))
+                        .at(&command.file, command.line),
+                    );
+                }
+                ty.resolve(&defined, &[]);
+            }
+        }
+        for def in &mut self.types {
+            let generics = def.generics.clone();
+            def.visit_types_mut(&mut |ty| ty.resolve(&defined, &generics));
+        }
+
+        let mut used = HashSet::new();
+        let mut pending = Vec::new();
+        for command in &self.commands {
+            for ty in command
+                .params
+                .iter()
+                .map(|param| &param.ty)
+                .chain([&command.ret])
+            {
+                ty.visit_names(&mut |name| pending.push(name.to_string()));
+            }
+        }
+        while let Some(name) = pending.pop() {
+            if !used.insert(name.clone()) {
+                continue;
+            }
+            if let Some(def) = self.types.iter().find(|def| def.name == name) {
+                def.visit_types(&mut |ty| {
+                    ty.visit_names(&mut |name| pending.push(name.to_string()))
+                });
+            }
+        }
+        self.types.retain(|def| used.contains(&def.name));
+
+        if self.uses_channel() && self.types.iter().any(|def| def.name == "Channel") {
+            self.diagnostics.push(Diagnostic::error(
+                "Type `Channel` has the name of the `Channel` of Tauri, which the generated files import, rename it",
+            ));
+        }
+    }
+
+    /// Whether a command or a type uses a `tauri::ipc::Channel`,
+    /// which the generated files import from the core module.
+    pub(crate) fn uses_channel(&self) -> bool {
+        let mut used = false;
+        let mut check = |ty: &TypeRef| used |= ty.has_channel();
+        for command in &self.commands {
+            command.params.iter().for_each(|param| check(&param.ty));
+            check(&command.ret);
+        }
+        for def in &self.types {
+            def.visit_types(&mut check);
+        }
+        used
+    }
+}
+
+/// The file of a module.
+struct SourceFile {
+    path: PathBuf,
+    /// The directory with the files of the modules declared in the file.
+    module_dir: PathBuf,
+    /// The conditions the module is compiled under, `None` if it isn't compiled.
+    gates: Option<Gates>,
+    /// The index of the crate root the module was reached from.
+    target: usize,
+}
+
+impl SourceFile {
+    /// A file that owns its directory, like `lib.rs` or `mod.rs`.
+    fn root(path: PathBuf) -> Self {
+        let module_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        SourceFile {
+            path,
+            module_dir,
+            gates: Some(Gates::default()),
+            target: 0,
+        }
+    }
+
+    /// The file of a module declared in `parent`, compiled under some conditions.
+    fn declared_in(self, parent: &SourceFile, gates: Option<Gates>) -> Self {
+        SourceFile {
+            gates,
+            target: parent.target,
+            ..self
+        }
+    }
+}
+
+/// The files read and parsed so far, so that each is read once.
+#[derive(Default)]
+struct Sources {
+    /// `None` for the files that couldn't be read or parsed.
+    files: HashMap<PathBuf, Option<syn::File>>,
+}
+
+impl Sources {
+    /// Reads and parses a file, skipping it with a warning if it can't be.
+    fn load(&mut self, path: &Path, diagnostics: &mut Vec<Diagnostic>) -> Option<&syn::File> {
+        self.files
+            .entry(path.to_path_buf())
+            .or_insert_with(|| {
+                println!("cargo:rerun-if-changed={}", path.display());
+                let content = std::fs::read_to_string(path)
+                    .map_err(|err| {
+                        diagnostics.push(
+                            Diagnostic::warning(format!("Skipping the file: {}", err))
+                                .in_file(path),
+                        )
+                    })
+                    .ok()?;
+                syn::parse_file(&content)
+                    .map_err(|err| {
+                        diagnostics.push(
+                            Diagnostic::warning(format!("Skipping the file: {}", err))
+                                .at(path, err.span().start().line),
+                        )
+                    })
+                    .ok()
+            })
+            .as_ref()
+    }
+}
+
+/// The conditions an item is compiled under, including those of the modules it's in.
+#[derive(Debug, Clone)]
+struct Gates {
+    platforms: Vec<Platform>,
+    /// The `#[cfg]` predicates, like `feature = "sync"`.
+    cfg: Vec<String>,
+}
+
+impl Default for Gates {
+    fn default() -> Self {
+        Gates {
+            platforms: Platform::ALL.to_vec(),
+            cfg: Vec::new(),
+        }
+    }
+}
+
+impl Gates {
+    /// Adds the `#[cfg]` attributes of an item, those in a `cfg_attr` included,
+    /// `None` if it isn't compiled at all.
+    fn restrict(&self, cfg: &Cfg, attrs: &[Attribute]) -> Option<Gates> {
+        let platforms = intersect(&self.platforms, &cfg.platforms(attrs));
+        if platforms.is_empty() {
+            return None;
+        }
+        let mut predicates = self.cfg.clone();
+        predicates.extend(cfg_predicates(&cfg.expand_attrs(attrs)));
+        Some(Gates {
+            platforms,
+            cfg: predicates,
+        })
+    }
+
+    /// Splits the platforms by the value of `key` on each of them,
+    /// like the file of a module that depends on the platform.
+    fn split_by<K: PartialEq>(&self, key: impl Fn(Platform) -> K) -> Vec<(K, Gates)> {
+        let mut split = Vec::<(K, Gates)>::new();
+        for &platform in &self.platforms {
+            let key = key(platform);
+            match split.iter_mut().find(|(other, _)| *other == key) {
+                Some((_, gates)) => gates.platforms.push(platform),
+                None => split.push((
+                    key,
+                    Gates {
+                        platforms: vec![platform],
+                        cfg: self.cfg.clone(),
+                    },
+                )),
+            }
+        }
+        split
+    }
+}
+
+/// Finds the commands in the sources of the crate in `manifest_dir`.
+pub(crate) fn parse_functions(manifest_dir: &Path, config: &Config) -> Result<Model, Error> {
+    let mut model = Model::default();
+    let detected = config
+        .tauri_version
+        .or_else(|| TauriVersion::detect(manifest_dir));
+    model.tauri_version = detected.unwrap_or_else(|| {
+        let version = TauriVersion::default();
+        model.diagnostics.push(Diagnostic::warning(format!(
+            "Could not determine the version of the `tauri` dependency, generating declarations for {:?}",
+            version
+        )));
+        version
+    });
+
+    let cfg = Cfg::from_env();
+    let mut sources = Sources::default();
+    let tree = module_tree(
+        crate_roots(manifest_dir)?,
+        &cfg,
+        &mut sources,
+        &mut model.diagnostics,
+    );
+    let files = match config.discovery {
+        Discovery::Files => source_files(
+            &normalize(&manifest_dir.join(&config.scan_root)),
+            config,
+            &mut model.diagnostics,
+        )?
+        .into_iter()
+        .filter_map(
+            |path| match tree.iter().find(|module| module.path == path) {
+                Some(module) => Some((path, module.gates.clone()?)),
+                // Not a module of the crate, like an example, which is compiled on its own.
+                None => Some((path, Gates::default())),
+            },
+        )
+        .collect::<Vec<_>>(),
+        Discovery::ModuleTree => tree
+            .iter()
+            .filter_map(|module| Some((module.path.clone(), module.gates.clone()?)))
+            .collect(),
+    };
+    // The types defined in the crate, to tell them from those of Tauri a glob import brings.
+    let mut crate_types = HashSet::new();
+    for (path, _) in &files {
+        if let Some(ast) = sources.load(path, &mut model.diagnostics) {
+            crate_types.extend(Imports::collect(&ast.items).local_types().iter().cloned());
+        }
+    }
+    let mut registered = None;
+    let mut plugin_names = Vec::new();
+
+    for (path, gates) in files {
+        let Some(ast) = sources.load(&path, &mut model.diagnostics) else {
+            continue;
+        };
+        // `#![cfg(...)]` at the top of the file.
+        let Some(gates) = gates.restrict(&cfg, &ast.attrs) else {
+            continue;
+        };
+
+        let scope = Scope {
+            cfg: &cfg,
+            imports: Imports::collect(&ast.items),
+            crate_types: &crate_types,
+            extractors: &config.extractors,
+            file: &path,
+        };
+        collect_items(&ast.items, &scope, &gates, &mut model);
+
+        let mut handlers = Handlers {
+            imports: &scope.imports,
+            file: &path,
+            registered: &mut registered,
+            plugin_names: &mut plugin_names,
+            diagnostics: &mut model.diagnostics,
+        };
+        handlers.visit_file(ast);
+    }
+
+    for flag in cfg.unknown_flags() {
+        model.diagnostics.push(Diagnostic::warning(format!(
+            "Unknown cfg `{}`, the items gated on it are treated as not compiled",
+            flag
+        )));
+    }
+
+    let files = model
+        .commands
+        .iter_mut()
+        .map(|command| &mut command.file)
+        .chain(
+            model
+                .diagnostics
+                .iter_mut()
+                .filter_map(|diagnostic| diagnostic.file.as_mut()),
+        );
+    for file in files {
+        if let Ok(relative) = file.strip_prefix(manifest_dir) {
+            *file = relative.to_path_buf();
+        }
+    }
+    let targets = tree
+        .into_iter()
+        .filter(|module| module.gates.is_some())
+        .map(|module| {
+            let path = match module.path.strip_prefix(manifest_dir) {
+                Ok(relative) => relative.to_path_buf(),
+                Err(_) => module.path,
+            };
+            (path, module.target)
+        })
+        .collect();
+    // Without `generate_handler!` the commands are registered elsewhere, e.g. by another crate.
+    if let Some(registered) = registered {
+        model.check_registered(&registered, config.registered_only);
+    }
+    model.merge_platforms(&targets);
+    model.sort_commands(config.sort_by);
+    model.resolve_types();
+    if model.commands.is_empty() {
+        model.diagnostics.push(Diagnostic::warning(
+            "No commands were found, the generated files declare none",
+        ));
+    }
+
+    if config.plugin {
+        let mut unique = HashSet::new();
+        plugin_names.retain(|name| unique.insert(name.clone()));
+        if config.plugin_name.is_none() && plugin_names.len() > 1 {
+            model.diagnostics.push(Diagnostic::warning(format!(
+                "Found several plugin names: {}, `{}` is used",
+                plugin_names.join(", "),
+                plugin_names[0]
+            )));
+        }
+        let name = config
+            .plugin_name
+            .clone()
+            .or(plugin_names.into_iter().next())
+            .ok_or_else(|| Error::config("Could not find the name of the plugin in `tauri::plugin::Builder::new`, set it with `Builder::plugin_name`"))?;
+        model.plugin = Some(name);
+    }
+    Ok(model)
+}
+
+/// Follows the `mod` declarations from the crate roots, returning the files of the modules
+/// with the conditions they are compiled under, `None` for those that aren't compiled.
+/// The conditions don't include the `#![cfg]` attributes of the files themselves.
+///
+/// A file declared by the modules of several targets is attributed to the first one reaching it.
+fn module_tree(
+    roots: Vec<PathBuf>,
+    cfg: &Cfg,
+    sources: &mut Sources,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<SourceFile> {
+    let mut pending = roots
+        .into_iter()
+        .enumerate()
+        .map(|(target, path)| SourceFile {
+            target,
+            ..SourceFile::root(path)
+        })
+        .collect::<VecDeque<_>>();
+    let mut tree = Vec::<SourceFile>::new();
+    while let Some(file) = pending.pop_front() {
+        if tree.iter().any(|module| module.path == file.path) {
+            continue;
+        }
+        if let Some(ast) = sources.load(&file.path, diagnostics) {
+            let gates = file
+                .gates
+                .as_ref()
+                .and_then(|gates| gates.restrict(cfg, &ast.attrs));
+            let mut modules = Modules {
+                cfg,
+                file: &file,
+                files: &mut pending,
+                diagnostics,
+            };
+            modules.collect(&ast.items, &file.module_dir, false, gates.as_ref());
+        }
+        tree.push(file);
+    }
+    tree
+}
+
+/// Finds the files of the modules declared in a file.
+struct Modules<'a> {
+    cfg: &'a Cfg,
+    file: &'a SourceFile,
+    files: &'a mut VecDeque<SourceFile>,
+    diagnostics: &'a mut Vec<Diagnostic>,
+}
+
+impl Modules<'_> {
+    /// Adds the files of the modules declared in the items, descending into inline modules.
+    ///
+    /// `dir` is the directory where the files are looked for, `inline` tells whether the items
+    /// are in an inline module and `gates` are the conditions they are compiled under.
+    fn collect(&mut self, items: &[Item], dir: &Path, inline: bool, gates: Option<&Gates>) {
+        for item in items {
+            let Item::Mod(module) = item else {
+                continue;
+            };
+            let name = module.ident.unraw().to_string();
+            // `#[cfg_attr(windows, path = "windows.rs")]` gives a file for each set of platforms.
+            let sources = match gates.and_then(|gates| gates.restrict(self.cfg, &module.attrs)) {
+                Some(gates) => gates
+                    .split_by(|platform| {
+                        path_attr(&self.cfg.expand_attrs_on(&module.attrs, platform))
+                    })
+                    .into_iter()
+                    .map(|(path, gates)| (path, Some(gates)))
+                    .collect(),
+                None => vec![(path_attr(&self.cfg.expand_attrs(&module.attrs)), None)],
+            };
+            for (path, gates) in sources {
+                match &module.content {
+                    Some((_, items)) => {
+                        let dir = match path {
+                            Some(path) => normalize(&dir.join(path)),
+                            None => dir.join(&name),
+                        };
+                        self.collect(items, &dir, true, gates.as_ref());
+                    }
+                    None => match module_source(&self.file.path, dir, inline, &name, path) {
+                        Some(file) => self.files.push_back(file.declared_in(self.file, gates)),
+                        // A module that isn't compiled doesn't need a file.
+                        None if gates.is_none() => {}
+                        None => self.diagnostics.push(
+                            Diagnostic::warning(format!(
+                                "Could not find the file of module `{}`",
+                                name
+                            ))
+                            .at(&self.file.path, module.ident.span().start().line),
+                        ),
+                    },
+                }
+            }
+        }
+    }
+}
+
+/// Collects the names of the commands registered with `generate_handler!`
+/// and the names of the plugins built with `tauri::plugin::Builder::new`.
+struct Handlers<'a> {
+    imports: &'a Imports,
+    /// The file being visited.
+    file: &'a Path,
+    /// `None` until an invocation is found.
+    registered: &'a mut Option<Vec<String>>,
+    plugin_names: &'a mut Vec<String>,
+    diagnostics: &'a mut Vec<Diagnostic>,
+}
+
+impl<'ast> Visit<'ast> for Handlers<'_> {
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        let segments = mac
+            .path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<_>>();
+        let is_generate_handler = match segments.as_slice() {
+            [name] => name == "generate_handler",
+            [tauri, name] => tauri == "tauri" && name == "generate_handler",
+            _ => false,
+        };
+
+        if is_generate_handler {
+            let registered = self.registered.get_or_insert_with(Vec::new);
+            match mac.parse_body_with(Punctuated::<syn::Path, Token![,]>::parse_terminated) {
+                Ok(paths) => registered.extend(paths.iter().filter_map(|path| {
+                    // `commands::get_weather`, or an imported alias of a command.
+                    match self.imports.resolve(path) {
+                        Some(resolved) => resolved.last().cloned(),
+                        None => path
+                            .segments
+                            .last()
+                            .map(|segment| segment.ident.unraw().to_string()),
+                    }
+                })),
+                Err(err) => self.diagnostics.push(
+                    Diagnostic::warning(format!("Could not parse `generate_handler!`: {}", err))
+                        .at(self.file, err.span().start().line),
+                ),
+            }
+        }
+        visit::visit_macro(self, mac);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast ExprCall) {
+        if let Expr::Path(func) = call.func.as_ref() {
+            let path = self.imports.resolve(&func.path).unwrap_or_else(|| {
+                func.path
+                    .segments
+                    .iter()
+                    .map(|segment| segment.ident.unraw().to_string())
+                    .collect()
+            });
+            let is_plugin_builder = path.ends_with(&[
+                "plugin".to_string(),
+                "Builder".to_string(),
+                "new".to_string(),
+            ]);
+            if let (
+                true,
+                Some(Expr::Lit(ExprLit {
+                    lit: Lit::Str(name),
+                    ..
+                })),
+            ) = (is_plugin_builder, call.args.first())
+            {
+                self.plugin_names.push(name.value());
+            }
+        }
+        visit::visit_expr_call(self, call);
+    }
+}
+
+/// What is needed to interpret the commands of a file.
+struct Scope<'a> {
+    cfg: &'a Cfg,
+    imports: Imports,
+    /// The names of the types defined in the scanned files.
+    crate_types: &'a HashSet<String>,
+    extractors: &'a [String],
+    file: &'a Path,
+}
+
+impl Scope<'_> {
+    /// Whether a parameter of this type is provided by Tauri rather than taken from `args`.
+    fn is_injected(&self, ty: &Type) -> bool {
+        let ty = match ty {
+            Type::Reference(reference) => reference.elem.as_ref(),
+            ty => ty,
+        };
+        let Type::Path(ty) = ty else {
+            return false;
+        };
+
+        match self.imports.resolve(&ty.path) {
+            Some(path) => {
+                let is_tauri = path.first().is_some_and(|first| first == "tauri")
+                    && path
+                        .last()
+                        .is_some_and(|last| INJECTED.contains(&last.as_str()));
+                is_tauri
+                    || self.extractors.iter().any(|extractor| {
+                        let extractor = extractor.trim_start_matches("::").split("::");
+                        let len = extractor.clone().count();
+                        path.len() >= len && path[path.len() - len..].iter().eq(extractor)
+                    })
+            }
+            // A name that comes from a glob import, like `use tauri::*;`, or the prelude.
+            // Other glob imports, like `use super::*;`, may bring the types of Tauri as well,
+            // but not when the crate defines a type of that name, like a `Request` struct.
+            None => {
+                let name = ty.path.segments[0].ident.to_string();
+                let is_tauri = self.imports.has_glob_from("tauri")
+                    || (self.imports.has_globs() && !self.crate_types.contains(&name));
+                (INJECTED.contains(&name.as_str()) && is_tauri)
+                    || self
+                        .extractors
+                        .iter()
+                        .any(|extractor| extractor.rsplit("::").next() == Some(name.as_str()))
+            }
+        }
+    }
+}
+
+/// Walks the items of a file, descending into inline modules, and collects
+/// the functions marked as Tauri commands and the serializable types.
+/// `gates` are the conditions the items are compiled under.
+fn collect_items(items: &[Item], scope: &Scope, gates: &Gates, model: &mut Model) {
+    for item in items {
+        let Some(gates) = gates.restrict(scope.cfg, item_attrs(item)) else {
+            continue;
+        };
+        let attrs = scope.cfg.expand_attrs(item_attrs(item));
+
+        match item {
+            Item::Fn(func) => {
+                // `#[cfg_attr(target_os = "macos", tauri::command)]` is a command on some platforms.
+                let command_gates = gates
+                    .split_by(|platform| {
+                        scope
+                            .cfg
+                            .expand_attrs_on(&func.attrs, platform)
+                            .iter()
+                            .any(is_command_attr)
+                    })
+                    .into_iter()
+                    .find_map(|(is_command, gates)| is_command.then_some(gates));
+                let attr = attrs.iter().find(|attr| is_command_attr(attr));
+                if let (Some(gates), Some(attr)) = (command_gates, attr) {
+                    let case = ArgumentCase::from_attr(attr).unwrap_or_else(|err| {
+                        model.diagnostics.push(
+                            Diagnostic::warning(format!(
+                                "Could not parse the command attribute: {}",
+                                err
+                            ))
+                            .at(scope.file, err.span().start().line),
+                        );
+                        ArgumentCase::default()
+                    });
+                    let command =
+                        parse_command(func, &attrs, scope, case, gates, &mut model.diagnostics);
+                    model.commands.push(command);
+                }
+            }
+            Item::Struct(ItemStruct { ident, .. }) | Item::Enum(ItemEnum { ident, .. }) => {
+                let def = TypeDef::from_item(item, scope.cfg, &scope.imports);
+                let varies = scope
+                    .cfg
+                    .platform_attrs(item_attrs(item))
+                    .iter()
+                    .any(is_serde_attr);
+                if def.is_some() && varies {
+                    model.diagnostics.push(
+                        Diagnostic::warning(format!(
+                            "Type `{}` has serde attributes that depend on the platform, they are applied on all of them",
+                            ident.unraw()
+                        ))
+                        .at(scope.file, ident.span().start().line),
+                    );
+                }
+                model.types.extend(def);
+            }
+            Item::Mod(module) => {
+                if let Some((_, items)) = &module.content {
+                    collect_items(items, scope, &gates, model);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Finds the file of the module declared as `mod name;`.
+fn module_source(
+    file: &Path,
+    dir: &Path,
+    inline: bool,
+    name: &str,
+    path: Option<String>,
+) -> Option<SourceFile> {
+    match path {
+        // `#[path]` is relative to the directory of the current file, or to the directory
+        // of the inline module it's in. The file it points to owns its directory.
+        Some(path) if inline => Some(SourceFile::root(normalize(&dir.join(path)))),
+        Some(path) => {
+            let base = file.parent().unwrap_or(dir);
+            Some(SourceFile::root(normalize(&base.join(path))))
+        }
+        None => module_file(dir, name).map(|path| SourceFile {
+            path,
+            module_dir: dir.join(name),
+            gates: None,
+            target: 0,
+        }),
+    }
+}
+
+fn item_attrs(item: &Item) -> &[Attribute] {
+    match item {
+        Item::Fn(item) => &item.attrs,
+        Item::Struct(item) => &item.attrs,
+        Item::Enum(item) => &item.attrs,
+        Item::Mod(item) => &item.attrs,
+        _ => &[],
+    }
+}
+
+/// The value of `#[path = "..."]`.
+fn path_attr(attrs: &[Attribute]) -> Option<String> {
+    attrs.iter().find_map(|attr| match &attr.meta {
+        Meta::NameValue(meta) if meta.path.is_ident("path") => match &meta.value {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(path),
+                ..
+            }) => Some(path.value()),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+/// How Tauri names the keys of the arguments, set with `#[tauri::command(rename_all = "...")]`.
+#[derive(Debug, Clone, Copy, Default)]
+enum ArgumentCase {
+    #[default]
+    Camel,
+    Snake,
+}
+
+impl ArgumentCase {
+    fn from_attr(attr: &Attribute) -> syn::Result<Self> {
+        let mut case = ArgumentCase::default();
+        if !matches!(attr.meta, Meta::List(_)) {
+            return Ok(case);
+        }
+
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename_all") {
+                let value = meta.value()?.parse::<syn::LitStr>()?;
+                case = match value.value().as_str() {
+                    "camelCase" => ArgumentCase::Camel,
+                    "snake_case" => ArgumentCase::Snake,
+                    _ => {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            "expected \"camelCase\" or \"snake_case\"",
+                        ))
+                    }
+                };
+            } else if meta.input.peek(Token![=]) {
+                // `root = "crate"` and alike.
+                meta.value()?.parse::<Expr>()?;
+            }
+            Ok(())
+        })?;
+        Ok(case)
+    }
+
+    fn apply(self, name: &str) -> String {
+        match self {
+            ArgumentCase::Camel => name.to_lower_camel_case(),
+            ArgumentCase::Snake => name.to_snake_case(),
+        }
+    }
+}
+
+fn parse_command(
+    func: &ItemFn,
+    attrs: &[Attribute],
+    scope: &Scope,
+    case: ArgumentCase,
+    gates: Gates,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> CommandInfo {
+    let name = func.sig.ident.unraw().to_string();
+    let line = func.sig.ident.span().start().line;
+    let generics = func
+        .sig
+        .generics
+        .type_params()
+        .map(|param| param.ident.to_string())
+        .collect::<Vec<_>>();
+    // Reports the parts of a type that can't be described and are typed as `any`.
+    let mut check = |position: String, ty: &Type| {
+        let mut untyped = Untyped {
+            generics: &generics,
+            parts: Vec::new(),
+        };
+        untyped.visit_type(ty);
+        for part in untyped.parts {
+            diagnostics.push(
+                Diagnostic::warning(format!(
+                    "{} of command `{}` has {}, it's typed as `any`",
+                    position, name, part
+                ))
+                .at(scope.file, line),
+            );
+        }
+    };
+
+    let params = func
+        .sig
+        .inputs
+        .iter()
+        .filter_map(|input| match input {
+            FnArg::Typed(arg) => Some(arg),
+            FnArg::Receiver(_) => None,
+        })
+        .filter(|arg| scope.cfg.is_enabled(&arg.attrs) && !scope.is_injected(&arg.ty))
+        .filter_map(|arg| match arg.pat.as_ref() {
+            Pat::Ident(pat) => {
+                let param = pat.ident.unraw().to_string();
+                check(format!("Parameter `{}`", param), &arg.ty);
+                let mut ty = TypeRef::from_type(&arg.ty, &scope.imports);
+                ty.erase(&generics);
+                Some(ParamInfo {
+                    name: case.apply(&param),
+                    ty,
+                })
+            }
+            _ => None,
+        })
+        .collect();
+
+    let mut ret = match &func.sig.output {
+        ReturnType::Default => TypeRef::Unit,
+        ReturnType::Type(_, ty) => {
+            if let Some(ty) = TypeRef::resolved_type(ty) {
+                check("The return type".to_string(), ty);
+            }
+            TypeRef::from_return_type(ty, &scope.imports)
+        }
+    };
+    ret.erase(&generics);
+
+    CommandInfo {
+        name,
+        params,
+        ret,
+        platforms: gates.platforms,
+        cfg: gates.cfg,
+        file: scope.file.to_path_buf(),
+        line,
+        docs: doc_comment(attrs),
+    }
+}
+
+/// Finds the parts of a type that can't be described in TypeScript:
+/// `impl Trait`, `dyn Trait`, generic parameters of the command and alike.
+struct Untyped<'a> {
+    generics: &'a [String],
+    parts: Vec<String>,
+}
+
+impl<'ast> Visit<'ast> for Untyped<'_> {
+    fn visit_type(&mut self, ty: &'ast Type) {
+        match ty {
+            Type::Path(path) if path.qself.is_none() => {
+                let generic = path
+                    .path
+                    .get_ident()
+                    .filter(|ident| self.generics.iter().any(|generic| *ident == generic));
+                match generic {
+                    Some(ident) => self.parts.push(format!("the generic type `{}`", ident)),
+                    None => visit::visit_type(self, ty),
+                }
+            }
+            Type::Path(_) => self.parts.push("a qualified path type".to_string()),
+            Type::ImplTrait(_) => self.parts.push("an `impl Trait` type".to_string()),
+            Type::TraitObject(_) => self.parts.push("a `dyn Trait` type".to_string()),
+            Type::BareFn(_)
+            | Type::Infer(_)
+            | Type::Macro(_)
+            | Type::Never(_)
+            | Type::Ptr(_)
+            | Type::Verbatim(_) => self
+                .parts
+                .push("a type that can't be described".to_string()),
+            _ => visit::visit_type(self, ty),
+        }
+    }
+}
+
+/// The text of `///` comments, which are `#[doc = "..."]` attributes.
+fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines = attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) if meta.path.is_ident("doc") => match &meta.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(doc), ..
+                }) => Some(doc.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').map(str::to_string).unwrap_or(line))
+        .collect::<Vec<_>>();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Joins predicates into one that holds if they all do.
+fn all_predicates(predicates: &[String]) -> String {
+    match predicates {
+        [predicate] => predicate.clone(),
+        predicates => format!("all({})", predicates.join(", ")),
+    }
+}
+
+/// The platforms in both lists.
+fn intersect(platforms: &[Platform], other: &[Platform]) -> Vec<Platform> {
+    platforms
+        .iter()
+        .copied()
+        .filter(|platform| other.contains(platform))
+        .collect()
+}
+
+/// `windows`, `windows and macos`, ...
+pub(crate) fn platform_list(platforms: &[Platform]) -> String {
+    let names = platforms
+        .iter()
+        .map(|platform| platform.target_os())
+        .collect::<Vec<_>>();
+    match names.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} and {}", rest.join(", "), last),
+        _ => names.join(""),
+    }
+}
+
+/// Matches `#[command]`, `#[tauri::command]` and `#[::tauri::command]`,
+/// with or without arguments.
+fn is_command_attr(attr: &Attribute) -> bool {
+    let segments = attr
+        .path()
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>();
+
+    match segments.as_slice() {
+        [command] => command == "command",
+        [tauri, command] => tauri == "tauri" && command == "command",
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::Severity;
+
+    /// Collects the commands and types of a file of the crate.
+    fn scan_file(file: &str, source: &str) -> Model {
+        let ast = syn::parse_file(source).unwrap();
+        let cfg = Cfg::default();
+        let imports = Imports::collect(&ast.items);
+        let crate_types = imports.local_types().clone();
+        let scope = Scope {
+            cfg: &cfg,
+            imports,
+            crate_types: &crate_types,
+            extractors: &["crate::auth::Session".to_string()],
+            file: Path::new(file),
+        };
+        let mut model = Model::default();
+        collect_items(&ast.items, &scope, &Gates::default(), &mut model);
+        model
+    }
+
+    fn scan(source: &str) -> Model {
+        scan_file("src/main.rs", source)
+    }
+
+    fn names(model: &Model) -> Vec<&str> {
+        model
+            .commands
+            .iter()
+            .map(|command| command.name.as_str())
+            .collect()
+    }
+
+    fn params(command: &CommandInfo) -> Vec<(&str, &TypeRef)> {
+        command
+            .params
+            .iter()
+            .map(|param| (param.name.as_str(), &param.ty))
+            .collect()
+    }
+
+    /// The commands registered with `generate_handler!` in a file.
+    fn registered(source: &str) -> Option<Vec<String>> {
+        let ast = syn::parse_file(source).unwrap();
+        let imports = Imports::collect(&ast.items);
+        let mut registered = None;
+        let mut handlers = Handlers {
+            imports: &imports,
+            file: Path::new("src/main.rs"),
+            registered: &mut registered,
+            plugin_names: &mut Vec::new(),
+            diagnostics: &mut Vec::new(),
+        };
+        handlers.visit_file(&ast);
+        registered
+    }
+
+    #[test]
+    fn command_attributes() {
+        let model = scan(
+            r#"
+            #[tauri::command]
+            fn plain() {}
+            #[::tauri::command]
+            fn absolute() {}
+            #[command]
+            fn imported() {}
+            #[tauri::command(rename_all = "snake_case")]
+            fn with_arguments() {}
+            #[tauri::command]
+            #[allow(unused_variables)]
+            fn attribute_after() {}
+            #[tokio::main]
+            fn other_attribute() {}
+            fn helper() {}
+            "#,
+        );
+        assert_eq!(
+            names(&model),
+            [
+                "plain",
+                "absolute",
+                "imported",
+                "with_arguments",
+                "attribute_after"
+            ]
+        );
+    }
+
+    #[test]
+    fn commands_outside_code() {
+        let model = scan(
+            r##"
+            // #[tauri::command]
+            // fn in_comment() {}
+            /* #[tauri::command] fn in_block_comment() {} */
+            /// ```
+            /// #[tauri::command]
+            /// fn in_doc_example() {}
+            /// ```
+            fn helper() -> &'static str {
+                "#[tauri::command] fn in_string() {}"
+            }
+            const RAW: &str = r#"#[tauri::command] fn in_raw_string() {}"#;
+            "##,
+        );
+        assert!(model.commands.is_empty());
+    }
+
+    #[test]
+    fn argument_keys() {
+        let model = scan(
+            r#"
+            #[tauri::command]
+            fn camel(user_id: u32, r#type: String, name: String) {}
+            #[tauri::command(rename_all = "snake_case")]
+            fn snake(user_id: u32, r#type: String) {}
+            #[tauri::command(rename_all = "kebab-case")]
+            fn unknown(user_id: u32) {}
+            "#,
+        );
+        let keys = |command: &CommandInfo| {
+            command
+                .params
+                .iter()
+                .map(|param| param.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(keys(&model.commands[0]), ["userId", "type", "name"]);
+        assert_eq!(keys(&model.commands[1]), ["user_id", "type"]);
+        assert_eq!(keys(&model.commands[2]), ["userId"]);
+        assert_eq!(model.diagnostics.len(), 1);
+        assert_eq!(model.diagnostics[0].line, Some(6));
+    }
+
+    #[test]
+    fn return_types() {
+        let model = scan(
+            r#"
+            #[tauri::command]
+            fn result() -> Result<String, String> {}
+            #[tauri::command]
+            fn alias() -> tauri::Result<Vec<u8>> {}
+            #[tauri::command]
+            async fn unit() -> Result<(), Error> {}
+            #[tauri::command]
+            fn optional() -> Option<bool> {}
+            #[tauri::command]
+            fn nothing() {}
+            "#,
+        );
+        let returns = model
+            .commands
+            .iter()
+            .map(|command| command.ret.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            returns,
+            [
+                TypeRef::String,
+                TypeRef::List(Box::new(TypeRef::Number)),
+                TypeRef::Unit,
+                TypeRef::Option(Box::new(TypeRef::Boolean)),
+                TypeRef::Unit,
+            ]
+        );
+    }
+
+    #[test]
+    fn injected_parameters() {
+        let model = scan(
+            r#"
+            use tauri::{AppHandle, State as Shared};
+            #[tauri::command]
+            fn explicit(app: AppHandle, state: Shared<'_, Db>, window: tauri::Window, id: u32) {}
+            #[tauri::command]
+            fn extractor(session: Session, id: u32) {}
+            "#,
+        );
+        assert_eq!(params(&model.commands[0]), [("id", &TypeRef::Number)]);
+        assert_eq!(params(&model.commands[1]), [("id", &TypeRef::Number)]);
+
+        // Through a glob import, like one re-exporting the imports of the parent module.
+        let model = scan(
+            r#"
+            use super::*;
+            #[tauri::command]
+            fn glob(state: State<'_, Db>, name: String) {}
+            "#,
+        );
+        assert_eq!(params(&model.commands[0]), [("name", &TypeRef::String)]);
+
+        // A type of the crate with the name of a type of Tauri.
+        let model = scan(
+            r#"
+            mod dto {
+                #[derive(serde::Deserialize)]
+                pub struct Request {}
+            }
+            use dto::*;
+            #[tauri::command]
+            fn send(request: Request) {}
+            "#,
+        );
+        assert_eq!(names(&model), ["send"]);
+        assert_eq!(model.commands[0].params.len(), 1);
+    }
+
+    #[test]
+    fn channel() {
+        let model = scan(
+            r#"
+            use tauri::ipc::Channel;
+            #[tauri::command]
+            fn download(on_progress: Channel<u8>) {}
+            "#,
+        );
+        assert_eq!(
+            params(&model.commands[0]),
+            [("onProgress", &TypeRef::Channel(Box::new(TypeRef::Number)))]
+        );
+
+        let model = scan(
+            r#"
+            #[derive(serde::Deserialize)]
+            struct Channel {
+                name: String,
+            }
+            #[tauri::command]
+            fn join(channel: Channel) {}
+            "#,
+        );
+        assert!(matches!(
+            &model.commands[0].params[0].ty,
+            TypeRef::Named { name, .. } if name == "Channel"
+        ));
+    }
+
+    #[test]
+    fn platform_attributes() {
+        let model = scan(
+            r#"
+            #[cfg_attr(target_os = "macos", tauri::command)]
+            fn menu() {}
+            #[cfg_attr(feature = "tray", tauri::command)]
+            fn tray() {}
+            #[derive(serde::Serialize)]
+            #[cfg_attr(windows, serde(rename_all = "camelCase"))]
+            struct Options {}
+            "#,
+        );
+        assert_eq!(names(&model), ["menu"]);
+        assert_eq!(model.commands[0].platforms, [Platform::Macos]);
+        assert_eq!(model.diagnostics.len(), 1);
+        assert_eq!(model.diagnostics[0].line, Some(8));
+    }
+
+    #[test]
+    fn registered_commands() {
+        assert_eq!(registered("fn main() {}"), None);
+        assert_eq!(
+            registered(
+                r#"
+                use commands::get_weather as weather;
+                fn main() {
+                    tauri::Builder::default()
+                        .invoke_handler(tauri::generate_handler![greet, commands::open, weather]);
+                }
+                "#
+            ),
+            Some(vec![
+                "greet".to_string(),
+                "open".to_string(),
+                "get_weather".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn duplicates() {
+        let mut model = scan(
+            r#"
+            #[cfg(windows)]
+            #[tauri::command]
+            fn open(path: String) {}
+            #[cfg(not(windows))]
+            #[tauri::command]
+            fn open(path: String) {}
+            #[tauri::command]
+            fn greet(name: String) {}
+            #[tauri::command]
+            fn greet() {}
+            "#,
+        );
+        let targets = HashMap::from([(PathBuf::from("src/main.rs"), 0)]);
+        model.merge_platforms(&targets);
+        assert_eq!(names(&model), ["open", "greet"]);
+        assert_eq!(model.commands[0].platforms, Platform::ALL);
+        assert_eq!(model.commands[1].params.len(), 1);
+        assert_eq!(model.diagnostics.len(), 1);
+        assert_eq!(model.diagnostics[0].severity, Severity::Error);
+        assert_eq!(model.diagnostics[0].line, Some(11));
+    }
+
+    #[test]
+    fn duplicates_outside_module_tree() {
+        let mut model = scan_file(
+            "examples/demo.rs",
+            r#"
+            #[tauri::command]
+            fn greet() {}
+            #[tauri::command]
+            fn demo() {}
+            "#,
+        );
+        model
+            .commands
+            .extend(scan("#[tauri::command] fn greet(name: String) {}").commands);
+        let targets = HashMap::from([(PathBuf::from("src/main.rs"), 0)]);
+        model.check_registered(&["greet".to_string()], true);
+        model.merge_platforms(&targets);
+        assert_eq!(names(&model), ["greet"]);
+        assert_eq!(model.commands[0].file, Path::new("src/main.rs"));
+        // Only the warning about `demo`, which isn't registered.
+        assert_eq!(model.diagnostics.len(), 1);
+        assert_eq!(model.diagnostics[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn sorting() {
+        let mut model = scan(
+            r#"
+            #[tauri::command]
+            fn zoom() {}
+            #[tauri::command]
+            fn alpha() {}
+            "#,
+        );
+        model.sort_commands(SortKey::Name);
+        assert_eq!(names(&model), ["alpha", "zoom"]);
+        model.sort_commands(SortKey::Location);
+        assert_eq!(names(&model), ["zoom", "alpha"]);
+    }
+}
@@ -0,0 +1,111 @@
+use std::path::{Path, PathBuf};
+
+use crate::{emit::EmittedFile, error::Error, parse::Model, write::write_if_changed};
+
+/// Marks the files generated here, the others in the directory are left alone.
+const HEADER: &str = "# Automatically generated - DO NOT EDIT!";
+
+/// The `allow-<command>` and `deny-<command>` permissions of every command
+/// in `permissions/autogenerated/commands/<command>.toml`, like Tauri plugins have them,
+/// and a default permission set allowing all of them in `permissions/autogenerated/default.toml`.
+pub(crate) fn permission_files(model: &Model) -> Vec<EmittedFile> {
+    let dir = Path::new("permissions").join("autogenerated");
+
+    let identifiers = model
+        .commands
+        .iter()
+        .map(|command| format!("\"allow-{}\"", slug(&command.name)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let default = EmittedFile {
+        path: dir.join("default.toml"),
+        content: format!(
+            "{HEADER}
+
+[default]
+description = \"Allows all the commands.\"
+permissions = [{identifiers}]
+"
+        ),
+    };
+
+    model
+        .commands
+        .iter()
+        .map(|command| EmittedFile {
+            path: dir.join("commands").join(format!("{}.toml", command.name)),
+            content: command_permissions(&command.name),
+        })
+        .chain([default])
+        .collect()
+}
+
+/// Writes the permission files under `manifest_dir`, and removes the files
+/// generated for the commands that are gone.
+pub(crate) fn write_permissions(manifest_dir: &Path, files: &[EmittedFile]) -> Result<(), Error> {
+    let obsolete = obsolete_permissions(manifest_dir, files)?;
+    for file in files {
+        write_if_changed(&manifest_dir.join(&file.path), &file.content)?;
+    }
+    for path in obsolete {
+        let path = manifest_dir.join(path);
+        std::fs::remove_file(&path).map_err(Error::io(&path))?;
+    }
+    Ok(())
+}
+
+/// The files generated for the commands that are gone, relative to `manifest_dir`.
+/// Files in the directory that weren't generated are left alone.
+pub(crate) fn obsolete_permissions(
+    manifest_dir: &Path,
+    files: &[EmittedFile],
+) -> Result<Vec<PathBuf>, Error> {
+    let dir = Path::new("permissions")
+        .join("autogenerated")
+        .join("commands");
+    let commands_dir = manifest_dir.join(&dir);
+    if !commands_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut obsolete = Vec::new();
+    let entries = std::fs::read_dir(&commands_dir).map_err(Error::io(&commands_dir))?;
+    for entry in entries {
+        let path = dir.join(entry.map_err(Error::io(&commands_dir))?.file_name());
+        let is_stale = path
+            .extension()
+            .is_some_and(|extension| extension == "toml")
+            && !files.iter().any(|file| file.path == path);
+        let is_generated = std::fs::read_to_string(manifest_dir.join(&path))
+            .is_ok_and(|content| content.starts_with(HEADER));
+        if is_stale && is_generated {
+            obsolete.push(path);
+        }
+    }
+    // The order of the entries depends on the file system.
+    obsolete.sort();
+    Ok(obsolete)
+}
+
+fn command_permissions(command: &str) -> String {
+    let slug = slug(command);
+    format!(
+        "{HEADER}
+
+[[permission]]
+identifier = \"allow-{slug}\"
+description = \"Enables the {command} command without any pre-configured scope.\"
+commands.allow = [\"{command}\"]
+
+[[permission]]
+identifier = \"deny-{slug}\"
+description = \"Denies the {command} command without any pre-configured scope.\"
+commands.deny = [\"{command}\"]
+"
+    )
+}
+
+/// Permission identifiers are in kebab-case: `get_weather` => `get-weather`.
+fn slug(command: &str) -> String {
+    command.replace('_', "-")
+}
@@ -0,0 +1,672 @@
+use std::collections::HashSet;
+
+use heck::{ToLowerCamelCase, ToUpperCamelCase};
+
+use crate::{
+    cfg::Platform,
+    config::Config,
+    parse::{platform_list, CommandInfo, Model},
+    typedef::{Field, Fields, Shape, Tagging, TypeDef, Variant},
+    types::TypeRef,
+    TauriVersion,
+};
+
+pub(crate) fn get_content(model: &Model, config: &Config) -> String {
+    let Model {
+        commands,
+        types,
+        plugin,
+        tauri_version: version,
+        ..
+    } = model;
+    let version = *version;
+    let plugin = plugin.as_deref();
+    let i = config.indent.unit();
+    let module = config.module.as_deref().unwrap_or(version.module());
+    let types = types
+        .iter()
+        .map(|def| format!("{}\n", type_definition(def, &i)))
+        .collect::<String>();
+    // An empty union isn't valid TypeScript.
+    let names = match commands.is_empty() {
+        true => " never".to_string(),
+        false => format!("\n{i}{i}  {}", command_union(commands.iter(), plugin, &i)),
+    };
+    let platform_commands = Platform::ALL
+        .into_iter()
+        .filter_map(|platform| {
+            let mut commands = commands
+                .iter()
+                .filter(|command| {
+                    command.is_platform_specific() && command.platforms.contains(&platform)
+                })
+                .peekable();
+            commands.peek()?;
+            Some(format!(
+                "{i}type {}Commands =\n{i}{i}  {};\n",
+                platform.target_os().to_upper_camel_case(),
+                command_union(commands, plugin, &i)
+            ))
+        })
+        .collect::<String>();
+    let args = commands
+        .iter()
+        .map(|command| {
+            format!(
+                "{}{i}{i}{}: {};\n",
+                platform_doc(command, &format!("{i}{i}")),
+                string_literal(&command_key(command, plugin)),
+                args_type(command)
+            )
+        })
+        .collect::<String>();
+    let returns = commands
+        .iter()
+        .map(|command| {
+            format!(
+                "{i}{i}{}: {};\n",
+                string_literal(&command_key(command, plugin)),
+                return_type(command)
+            )
+        })
+        .collect::<String>();
+    let (imports, options) = match version {
+        TauriVersion::V1 => ("InvokeArgs", ""),
+        TauriVersion::V2 => ("InvokeArgs, InvokeOptions", ", options?: InvokeOptions"),
+    };
+    let imports = match model.uses_channel() {
+        true => format!("{imports}, Channel"),
+        false => imports.to_string(),
+    };
+
+    format!(
+"import type {{ {imports} }} from '{module}';
+{types}declare module '{module}' {{
+{i}type Commands ={names};
+{platform_commands}{i}interface Args extends Record<Commands, InvokeArgs> {{
+{args}{i}}}
+{i}interface Returns extends Record<Commands, unknown> {{
+{returns}{i}}}
+{i}type InvokeParams<C extends Commands> = {{}} extends Args[C]
+{i}{i}? [args?: Args[C]{options}]
+{i}{i}: [args: Args[C]{options}];
+{i}function invoke<C extends Commands>(cmd: C, ...params: InvokeParams<C>): Promise<Returns[C]>;
+}}")
+}
+
+/// A module of typed functions calling the commands, like the `guest-js` of Tauri plugins.
+pub(crate) fn get_bindings(model: &Model, config: &Config) -> String {
+    // Unused imports are errors with `noUnusedLocals`, the file is only kept a module.
+    if model.commands.is_empty() {
+        return "export {};\n".to_string();
+    }
+    let version = model.tauri_version;
+    let i = config.indent.unit();
+    let module = config.module.as_deref().unwrap_or(version.module());
+    let channel = match model.uses_channel() {
+        true => ", Channel",
+        false => "",
+    };
+    let imports = match version {
+        TauriVersion::V1 => format!("import {{ invoke }} from '{module}';\n"),
+        TauriVersion::V2 => format!(
+            "import {{ invoke }} from '{module}';\nimport type {{ InvokeOptions{channel} }} from '{module}';\n"
+        ),
+    };
+    let types = model
+        .types
+        .iter()
+        .map(|def| format!("\n{}\n", type_definition(def, &i)))
+        .collect::<String>();
+    let functions = model
+        .commands
+        .iter()
+        .zip(binding_names(&model.commands, &model.types))
+        .map(|(command, (function, args_name))| {
+            let args = match command.params.is_empty() {
+                true => String::new(),
+                false => format!("\n{}\n", args_alias(command, &args_name, &i)),
+            };
+            let function = binding_function(
+                command,
+                &function,
+                &args_name,
+                model.plugin.as_deref(),
+                version,
+                &i,
+            );
+            format!("{args}\n{function}")
+        })
+        .collect::<String>();
+    format!("{imports}{types}{functions}")
+}
+
+/// The names of the function calling each command, in camelCase, and of the type of its `args`.
+/// A trailing `_` is added to a name that is a reserved word of JavaScript, like `delete_`,
+/// or that is taken in the module by an import, a type or the function of another command,
+/// like `getWeather_` for `getWeather` after `get_weather`.
+fn binding_names(commands: &[CommandInfo], types: &[TypeDef]) -> Vec<(String, String)> {
+    let mut taken = types.iter().map(|def| def.name.clone()).collect::<HashSet<_>>();
+    let mut escape = |mut name: String| {
+        while RESERVED_WORDS.contains(&name.as_str())
+            || IMPORTED_NAMES.contains(&name.as_str())
+            || taken.contains(&name)
+        {
+            name.push('_');
+        }
+        taken.insert(name.clone());
+        name
+    };
+    commands
+        .iter()
+        .map(|command| {
+            let function = escape(command.name.to_lower_camel_case());
+            let args = escape(format!("{}Args", command.name.to_upper_camel_case()));
+            (function, args)
+        })
+        .collect()
+}
+
+/// The type of the `args` of a command, e.g. `export type GetWeatherArgs = { city: string; };`.
+/// Not an interface, which wouldn't be assignable to `InvokeArgs` for the lack of an index signature.
+fn args_alias(command: &CommandInfo, name: &str, i: &str) -> String {
+    let fields = command
+        .params
+        .iter()
+        .map(|param| {
+            let optional = match param.ty {
+                TypeRef::Option(_) => "?",
+                _ => "",
+            };
+            format!("{i}{}{}: {};\n", property_name(&param.name), optional, ts_type(&param.ty))
+        })
+        .collect::<String>();
+    format!("export type {} = {{\n{}}};", name, fields)
+}
+
+/// A function calling a command, e.g.
+/// `export async function getWeather(args: GetWeatherArgs): Promise<string> { ... }`.
+fn binding_function(
+    command: &CommandInfo,
+    name: &str,
+    args_name: &str,
+    plugin: Option<&str>,
+    version: TauriVersion,
+    i: &str,
+) -> String {
+    let mut params = Vec::new();
+    let args = match command.params.is_empty() {
+        true => "{}",
+        false => {
+            // All the arguments can be left out.
+            let default = match command
+                .params
+                .iter()
+                .all(|param| matches!(param.ty, TypeRef::Option(_)))
+            {
+                true => " = {}",
+                false => "",
+            };
+            params.push(format!("args: {}{}", args_name, default));
+            "args"
+        }
+    };
+    let call_args = match version {
+        TauriVersion::V1 => args.to_string(),
+        TauriVersion::V2 => {
+            params.push("options?: InvokeOptions".to_string());
+            format!("{args}, options")
+        }
+    };
+    let ret = return_type(command);
+
+    format!(
+        "{}export async function {}({}): Promise<{ret}> {{\n{i}return await invoke<{ret}>({}, {call_args});\n}}\n",
+        platform_doc(command, ""),
+        name,
+        params.join(", "),
+        string_literal(&command_key(command, plugin))
+    )
+}
+
+/// The names the module of functions imports.
+const IMPORTED_NAMES: &[&str] = &["invoke", "InvokeOptions", "Channel"];
+
+/// The words that can't name a function in a module, which is in strict mode.
+const RESERVED_WORDS: &[&str] = &[
+    "arguments", "await", "break", "case", "catch", "class", "const", "continue", "debugger",
+    "default", "delete", "do", "else", "enum", "eval", "export", "extends", "false", "finally",
+    "for", "function", "if", "implements", "import", "in", "instanceof", "interface", "let", "new",
+    "null", "package", "private", "protected", "public", "return", "static", "super", "switch",
+    "this", "throw", "true", "try", "typeof", "var", "void", "while", "with", "yield",
+];
+
+/// The string a command is invoked with, `plugin:<name>|<command>` for the commands of a plugin.
+fn command_key(command: &CommandInfo, plugin: Option<&str>) -> String {
+    match plugin {
+        Some(plugin) => format!("plugin:{}|{}", plugin, command.name),
+        None => command.name.clone(),
+    }
+}
+
+/// A comment telling the platforms a platform-specific command is available on.
+fn platform_doc(command: &CommandInfo, indent: &str) -> String {
+    match command.is_platform_specific() {
+        true => format!(
+            "{indent}/** Only available on {}. */\n",
+            platform_list(&command.platforms)
+        ),
+        false => String::new(),
+    }
+}
+
+/// The names of the commands as a union of string literals, one per line.
+fn command_union<'a>(
+    commands: impl Iterator<Item = &'a CommandInfo>,
+    plugin: Option<&str>,
+    i: &str,
+) -> String {
+    commands
+        .map(|command| string_literal(&command_key(command, plugin)))
+        .collect::<Vec<_>>()
+        .join(&format!("\n{i}{i}| "))
+}
+
+/// The type of the `args` object of a command, e.g. `{ city: string; days?: number | null }`.
+fn args_type(command: &CommandInfo) -> String {
+    if command.params.is_empty() {
+        return "Record<string, never>".to_string();
+    }
+
+    let fields = command
+        .params
+        .iter()
+        .map(|param| match &param.ty {
+            // Tauri deserializes a missing argument as `None`.
+            TypeRef::Option(_) => format!("{}?: {}", param.name, ts_type(&param.ty)),
+            ty => format!("{}: {}", param.name, ts_type(ty)),
+        })
+        .collect::<Vec<_>>()
+        .join("; ");
+    format!("{{ {} }}", fields)
+}
+
+/// The type the promise returned by `invoke` resolves with.
+fn return_type(command: &CommandInfo) -> String {
+    match command.ret {
+        TypeRef::Unit => "void".to_string(),
+        ref ty => ts_type(ty),
+    }
+}
+
+fn ts_type(ty: &TypeRef) -> String {
+    match ty {
+        TypeRef::String => "string".to_string(),
+        TypeRef::Number => "number".to_string(),
+        TypeRef::Boolean => "boolean".to_string(),
+        TypeRef::Unit => "null".to_string(),
+        TypeRef::Option(inner) => format!("{} | null", ts_type(inner)),
+        TypeRef::List(inner) => match inner.as_ref() {
+            TypeRef::Option(_) => format!("({})[]", ts_type(inner)),
+            inner => format!("{}[]", ts_type(inner)),
+        },
+        TypeRef::Tuple(items) => format!(
+            "[{}]",
+            items.iter().map(ts_type).collect::<Vec<_>>().join(", ")
+        ),
+        TypeRef::Map(key, value) => {
+            let key = match key.as_ref() {
+                TypeRef::Number => "number",
+                _ => "string",
+            };
+            format!("Record<{}, {}>", key, ts_type(value))
+        }
+        TypeRef::Channel(inner) => format!("Channel<{}>", ts_type(inner)),
+        TypeRef::Named { name, args } if args.is_empty() => name.clone(),
+        TypeRef::Named { name, args } => format!(
+            "{}<{}>",
+            name,
+            args.iter().map(ts_type).collect::<Vec<_>>().join(", ")
+        ),
+        TypeRef::Any => "any".to_string(),
+    }
+}
+
+/// An exported declaration of a serializable type, e.g. `export interface Weather { city: string; }`.
+fn type_definition(def: &TypeDef, indent: &str) -> String {
+    let name = match def.generics.as_slice() {
+        [] => def.name.clone(),
+        generics => format!("{}<{}>", def.name, generics.join(", ")),
+    };
+
+    match &def.shape {
+        Shape::Struct {
+            fields: Fields::Named(fields),
+            tag,
+        } if tag.is_none() && fields.iter().all(|field| !field.flatten) => {
+            let fields = fields
+                .iter()
+                .map(|field| format!("{}{};\n", indent, field_declaration(field)))
+                .collect::<String>();
+            format!("export interface {} {{\n{}}}", name, fields)
+        }
+        Shape::Struct { fields, tag } => {
+            let tag = tag
+                .as_ref()
+                .map(|(tag, value)| format!("{}: {}", property_name(tag), string_literal(value)));
+            format!("export type {} = {};", name, fields_type(fields, tag))
+        }
+        Shape::Enum { variants, tagging } => {
+            let variants = variants
+                .iter()
+                .map(|variant| variant_type(variant, tagging))
+                .collect::<Vec<_>>();
+            let variants = match variants.is_empty() {
+                true => "never".to_string(),
+                false => variants.join(" | "),
+            };
+            format!("export type {} = {};", name, variants)
+        }
+    }
+}
+
+/// The type of a value serialized from fields, with `extra` properties (like a tag) prepended.
+fn fields_type(fields: &Fields, extra: Option<String>) -> String {
+    match fields {
+        Fields::Named(fields) => {
+            let properties = extra
+                .into_iter()
+                .chain(
+                    fields
+                        .iter()
+                        .filter(|field| !field.flatten)
+                        .map(field_declaration),
+                )
+                .collect::<Vec<_>>();
+            let object = match properties.is_empty() {
+                true => None,
+                false => Some(format!("{{ {} }}", properties.join("; "))),
+            };
+            let flattened = fields
+                .iter()
+                .filter(|field| field.flatten)
+                .map(|field| flattened_type(&field.ty));
+            let parts = object.into_iter().chain(flattened).collect::<Vec<_>>();
+            match parts.is_empty() {
+                true => "Record<string, never>".to_string(),
+                false => parts.join(" & "),
+            }
+        }
+        Fields::Unnamed(types) => {
+            let value = match types.as_slice() {
+                [ty] => ts_type(ty),
+                types => ts_type(&TypeRef::Tuple(types.to_vec())),
+            };
+            match extra {
+                Some(extra) => format!("{{ {} }} & {}", extra, value),
+                None => value,
+            }
+        }
+        Fields::Unit => match extra {
+            Some(extra) => format!("{{ {} }}", extra),
+            None => "null".to_string(),
+        },
+    }
+}
+
+/// A flattened field contributes its own fields, a flattened `Option` - maybe none of them.
+fn flattened_type(ty: &TypeRef) -> String {
+    match ty {
+        TypeRef::Option(inner) => format!("Partial<{}>", ts_type(inner)),
+        ty => ts_type(ty),
+    }
+}
+
+fn variant_type(variant: &Variant, tagging: &Tagging) -> String {
+    let name = string_literal(&variant.name);
+    if variant.untagged {
+        return fields_type(&variant.fields, None);
+    }
+
+    match tagging {
+        Tagging::External => match &variant.fields {
+            Fields::Unit => name,
+            fields => format!("{{ {}: {} }}", property_name(&variant.name), fields_type(fields, None)),
+        },
+        Tagging::Internal { tag } => {
+            fields_type(&variant.fields, Some(format!("{}: {}", property_name(tag), name)))
+        }
+        Tagging::Adjacent { tag, content } => match &variant.fields {
+            Fields::Unit => format!("{{ {}: {} }}", property_name(tag), name),
+            fields => format!(
+                "{{ {}: {}; {}: {} }}",
+                property_name(tag),
+                name,
+                property_name(content),
+                fields_type(fields, None)
+            ),
+        },
+        Tagging::Untagged => fields_type(&variant.fields, None),
+    }
+}
+
+fn field_declaration(field: &Field) -> String {
+    let optional = if field.optional { "?" } else { "" };
+    format!("{}{}: {}", property_name(&field.name), optional, ts_type(&field.ty))
+}
+
+/// A property name, quoted if it isn't a valid identifier, e.g. after `rename_all = "kebab-case"`.
+fn property_name(name: &str) -> String {
+    let mut chars = name.chars();
+    let is_identifier = chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_' || first == '$')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '$');
+    match is_identifier {
+        true => name.to_string(),
+        false => string_literal(name),
+    }
+}
+
+fn string_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::{cfg::Cfg, imports::Imports};
+
+    fn type_def(source: &str) -> TypeDef {
+        let item = syn::parse_str(source).unwrap();
+        TypeDef::from_item(&item, &Cfg::default(), &Imports::default()).unwrap()
+    }
+
+    /// The declaration of the type defined in `source`.
+    fn define(source: &str) -> String {
+        type_definition(&type_def(source), "  ")
+    }
+
+    fn command(name: &str) -> CommandInfo {
+        CommandInfo {
+            name: name.to_string(),
+            params: Vec::new(),
+            ret: TypeRef::Unit,
+            platforms: Platform::ALL.to_vec(),
+            cfg: Vec::new(),
+            file: PathBuf::from("src/main.rs"),
+            line: 1,
+            docs: None,
+        }
+    }
+
+    #[test]
+    fn binding_names_are_unique() {
+        let commands = ["delete", "invoke", "get_weather", "getWeather", "get_weather_"].map(command);
+        let types = [type_def("#[derive(Serialize)] struct GetWeatherArgs;")];
+        let names = binding_names(&commands, &types);
+        let names = names
+            .iter()
+            .map(|(function, args)| (function.as_str(), args.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                ("delete_", "DeleteArgs"),
+                ("invoke_", "InvokeArgs"),
+                ("getWeather", "GetWeatherArgs_"),
+                ("getWeather_", "GetWeatherArgs__"),
+                ("getWeather__", "GetWeatherArgs___"),
+            ]
+        );
+    }
+
+    #[test]
+    fn external_tagging() {
+        assert_eq!(
+            define(
+                "#[derive(Serialize)]
+                enum Shape { Empty, Circle(f64), Point(f64, f64), Rect { w: f64, h: f64 } }"
+            ),
+            "export type Shape = 'Empty' | { Circle: number } | { Point: [number, number] } | { Rect: { w: number; h: number } };"
+        );
+        assert_eq!(
+            define(
+                "#[derive(Serialize)]
+                enum Setting { Known(u8), #[serde(untagged)] Other(String) }"
+            ),
+            "export type Setting = { Known: number } | string;"
+        );
+    }
+
+    #[test]
+    fn internal_tagging() {
+        assert_eq!(
+            define(
+                r#"#[derive(Serialize)]
+                #[serde(tag = "type", rename_all = "camelCase")]
+                enum Event { Started, InProgress { pct: u8 }, Finished(Summary) }"#
+            ),
+            "export type Event = { type: 'started' } | { type: 'inProgress'; pct: number } | { type: 'finished' } & Summary;"
+        );
+    }
+
+    #[test]
+    fn adjacent_tagging() {
+        assert_eq!(
+            define(
+                r#"#[derive(Serialize)]
+                #[serde(tag = "t", content = "c")]
+                enum Message { Ping, Text(String), Move { x: i32 } }"#
+            ),
+            "export type Message = { t: 'Ping' } | { t: 'Text'; c: string } | { t: 'Move'; c: { x: number } };"
+        );
+    }
+
+    #[test]
+    fn untagged() {
+        assert_eq!(
+            define(
+                "#[derive(Serialize)]
+                #[serde(untagged)]
+                enum Id { Number(u64), Name(String), Unknown }"
+            ),
+            "export type Id = number | string | null;"
+        );
+        assert_eq!(
+            define("#[derive(Serialize)] enum Never {}"),
+            "export type Never = never;"
+        );
+    }
+
+    #[test]
+    fn flatten() {
+        assert_eq!(
+            define(
+                "#[derive(Serialize)]
+                struct Page {
+                    title: String,
+                    #[serde(flatten)]
+                    meta: Meta,
+                    #[serde(flatten)]
+                    extra: Option<Extra>,
+                }"
+            ),
+            "export type Page = { title: string } & Meta & Partial<Extra>;"
+        );
+        assert_eq!(
+            define(
+                "#[derive(Serialize)]
+                struct Wrapper { #[serde(flatten)] inner: HashMap<String, u8> }"
+            ),
+            "export type Wrapper = Record<string, number>;"
+        );
+    }
+
+    #[test]
+    fn struct_tag() {
+        assert_eq!(
+            define(
+                r#"#[derive(Serialize)]
+                #[serde(tag = "kind")]
+                struct Circle { radius: f64 }"#
+            ),
+            "export type Circle = { kind: 'Circle'; radius: number };"
+        );
+        assert_eq!(
+            define(
+                r#"#[derive(Serialize)]
+                #[serde(tag = "kind", rename = "circle")]
+                struct Circle;"#
+            ),
+            "export type Circle = { kind: 'circle' };"
+        );
+    }
+
+    #[test]
+    fn transparent() {
+        assert_eq!(
+            define("#[derive(Serialize)] #[serde(transparent)] struct UserId(u64);"),
+            "export type UserId = number;"
+        );
+        assert_eq!(
+            define(
+                "#[derive(Serialize)]
+                #[serde(transparent)]
+                struct Name { #[serde(skip)] cache: u8, value: String }"
+            ),
+            "export type Name = string;"
+        );
+    }
+
+    #[test]
+    fn skip_and_default() {
+        assert_eq!(
+            define(
+                r#"#[derive(Serialize)]
+                struct Settings<T> {
+                    #[serde(skip)]
+                    cache: Vec<u8>,
+                    #[serde(default)]
+                    theme: String,
+                    #[serde(skip_serializing_if = "Option::is_none")]
+                    note: Option<String>,
+                    #[serde(rename = "user-name")]
+                    name: String,
+                    value: T,
+                }"#
+            ),
+            "export interface Settings<T> {\n  theme?: string;\n  note?: string | null;\n  'user-name': string;\n  value: T;\n}"
+        );
+        assert_eq!(
+            define(
+                "#[derive(Serialize)]
+                #[serde(default)]
+                struct Options { limit: u32, offset: u32 }"
+            ),
+            "export interface Options {\n  limit?: number;\n  offset?: number;\n}"
+        );
+    }
+}
@@ -0,0 +1,593 @@
+use serde::Serialize;
+use syn::{ext::IdentExt, meta::ParseNestedMeta, Attribute, Expr, Item, LitStr, Token};
+
+use crate::{cfg::Cfg, imports::Imports, types::TypeRef};
+
+/// A struct or an enum deriving `Serialize` or `Deserialize`, described the way
+/// serde represents it in JSON.
+#[derive(Debug, Clone, Serialize)]
+#[non_exhaustive]
+pub struct TypeDef {
+    pub name: String,
+    pub generics: Vec<String>,
+    pub shape: Shape,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Shape {
+    Struct {
+        fields: Fields,
+        /// `#[serde(tag = "...")]` on a struct adds a field holding the name of the struct.
+        tag: Option<(String, String)>,
+    },
+    Enum {
+        variants: Vec<Variant>,
+        tagging: Tagging,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", content = "of", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Fields {
+    /// `{ a: A, b: B }`, serialized as an object.
+    Named(Vec<Field>),
+    /// `(A, B)`, serialized as an array, or as the value itself if there's only one field.
+    Unnamed(Vec<TypeRef>),
+    /// Serialized as `null`.
+    Unit,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[non_exhaustive]
+pub struct Field {
+    /// The key of the field in JSON.
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: TypeRef,
+    /// The key may be missing.
+    pub optional: bool,
+    /// The fields of the value are inlined into the parent object.
+    pub flatten: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[non_exhaustive]
+pub struct Variant {
+    /// The name of the variant in JSON.
+    pub name: String,
+    pub fields: Fields,
+    /// `#[serde(untagged)]` on a variant.
+    pub untagged: bool,
+}
+
+/// The [enum representation] chosen with serde attributes.
+///
+/// [enum representation]: https://serde.rs/enum-representations.html
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Tagging {
+    External,
+    Internal { tag: String },
+    Adjacent { tag: String, content: String },
+    Untagged,
+}
+
+impl TypeDef {
+    /// Describes a struct or an enum, if it derives `Serialize` or `Deserialize`.
+    pub(crate) fn from_item(item: &Item, cfg: &Cfg, imports: &Imports) -> Option<Self> {
+        let (ident, generics, attrs) = match item {
+            Item::Struct(item) => (&item.ident, &item.generics, &item.attrs),
+            Item::Enum(item) => (&item.ident, &item.generics, &item.attrs),
+            _ => return None,
+        };
+        let attrs = cfg.expand_attrs(attrs);
+        if !attrs.iter().any(is_serde_derive) {
+            return None;
+        }
+
+        let name = ident.unraw().to_string();
+        let generics = generics
+            .type_params()
+            .map(|param| param.ident.unraw().to_string())
+            .collect();
+        let container = SerdeAttrs::parse(&attrs);
+
+        let shape = match item {
+            Item::Struct(item) => {
+                let fields = if container.transparent {
+                    // Serialized as its only field that isn't skipped.
+                    let field = item
+                        .fields
+                        .iter()
+                        .filter_map(|field| {
+                            enabled_attrs(&field.attrs, cfg).map(|attrs| (field, attrs))
+                        })
+                        .map(|(field, attrs)| (field, SerdeAttrs::parse(&attrs)))
+                        .find(|(_, attrs)| !attrs.skip);
+                    Fields::Unnamed(
+                        field
+                            .map(|(field, attrs)| field_type(field, &attrs, imports))
+                            .into_iter()
+                            .collect(),
+                    )
+                } else {
+                    parse_fields(
+                        &item.fields,
+                        container.rename_all,
+                        container.default,
+                        cfg,
+                        imports,
+                    )
+                };
+                let tag = container.tag.map(|tag| {
+                    (
+                        tag,
+                        container.rename.clone().unwrap_or_else(|| name.clone()),
+                    )
+                });
+                Shape::Struct { fields, tag }
+            }
+            Item::Enum(item) => {
+                let variants = item
+                    .variants
+                    .iter()
+                    .filter_map(|variant| {
+                        let attrs = SerdeAttrs::parse(&enabled_attrs(&variant.attrs, cfg)?);
+                        if attrs.skip {
+                            return None;
+                        }
+                        let variant_name = variant.ident.unraw().to_string();
+                        let name = match (attrs.rename, container.rename_all) {
+                            (Some(rename), _) => rename,
+                            (None, Some(rule)) => rule.apply_to_variant(&variant_name),
+                            (None, None) => variant_name,
+                        };
+                        let rename_all = attrs.rename_all.or(container.rename_all_fields);
+                        Some(Variant {
+                            name,
+                            fields: parse_fields(&variant.fields, rename_all, false, cfg, imports),
+                            untagged: attrs.untagged,
+                        })
+                    })
+                    .collect();
+                let tagging = match (container.tag, container.content) {
+                    _ if container.untagged => Tagging::Untagged,
+                    (Some(tag), Some(content)) => Tagging::Adjacent { tag, content },
+                    (Some(tag), None) => Tagging::Internal { tag },
+                    _ => Tagging::External,
+                };
+                Shape::Enum { variants, tagging }
+            }
+            _ => unreachable!(),
+        };
+
+        Some(TypeDef {
+            name,
+            generics,
+            shape,
+        })
+    }
+
+    /// Calls `f` with the type of every field.
+    pub(crate) fn visit_types(&self, f: &mut impl FnMut(&TypeRef)) {
+        let visit_fields = |fields: &Fields, f: &mut dyn FnMut(&TypeRef)| match fields {
+            Fields::Named(fields) => fields.iter().for_each(|field| f(&field.ty)),
+            Fields::Unnamed(types) => types.iter().for_each(&mut *f),
+            Fields::Unit => {}
+        };
+        match &self.shape {
+            Shape::Struct { fields, .. } => visit_fields(fields, f),
+            Shape::Enum { variants, .. } => variants
+                .iter()
+                .for_each(|variant| visit_fields(&variant.fields, f)),
+        }
+    }
+
+    /// Calls `f` with the type of every field, allowing to change it.
+    pub(crate) fn visit_types_mut(&mut self, f: &mut impl FnMut(&mut TypeRef)) {
+        let visit_fields = |fields: &mut Fields, f: &mut dyn FnMut(&mut TypeRef)| match fields {
+            Fields::Named(fields) => fields.iter_mut().for_each(|field| f(&mut field.ty)),
+            Fields::Unnamed(types) => types.iter_mut().for_each(&mut *f),
+            Fields::Unit => {}
+        };
+        match &mut self.shape {
+            Shape::Struct { fields, .. } => visit_fields(fields, f),
+            Shape::Enum { variants, .. } => variants
+                .iter_mut()
+                .for_each(|variant| visit_fields(&mut variant.fields, f)),
+        }
+    }
+}
+
+fn parse_fields(
+    fields: &syn::Fields,
+    rename_all: Option<RenameRule>,
+    default: bool,
+    cfg: &Cfg,
+    imports: &Imports,
+) -> Fields {
+    match fields {
+        syn::Fields::Named(fields) => Fields::Named(
+            fields
+                .named
+                .iter()
+                .filter_map(|field| {
+                    let attrs = SerdeAttrs::parse(&enabled_attrs(&field.attrs, cfg)?);
+                    if attrs.skip {
+                        return None;
+                    }
+                    let field_name = field.ident.as_ref()?.unraw().to_string();
+                    let name = match (&attrs.rename, rename_all) {
+                        (Some(rename), _) => rename.clone(),
+                        (None, Some(rule)) => rule.apply_to_field(&field_name),
+                        (None, None) => field_name,
+                    };
+                    Some(Field {
+                        name,
+                        ty: field_type(field, &attrs, imports),
+                        optional: default || attrs.default || attrs.skip_one_way,
+                        flatten: attrs.flatten,
+                    })
+                })
+                .collect(),
+        ),
+        syn::Fields::Unnamed(fields) => Fields::Unnamed(
+            fields
+                .unnamed
+                .iter()
+                .filter_map(|field| {
+                    let attrs = SerdeAttrs::parse(&enabled_attrs(&field.attrs, cfg)?);
+                    (!attrs.skip).then(|| field_type(field, &attrs, imports))
+                })
+                .collect(),
+        ),
+        syn::Fields::Unit => Fields::Unit,
+    }
+}
+
+/// The attributes of a field or a variant with `cfg_attr` expanded,
+/// or `None` if it's not compiled.
+fn enabled_attrs(attrs: &[Attribute], cfg: &Cfg) -> Option<Vec<Attribute>> {
+    cfg.is_enabled(attrs).then(|| cfg.expand_attrs(attrs))
+}
+
+fn field_type(field: &syn::Field, attrs: &SerdeAttrs, imports: &Imports) -> TypeRef {
+    if attrs.custom {
+        // A custom (de)serializer, the shape of the value is unknown.
+        TypeRef::Any
+    } else {
+        TypeRef::from_type(&field.ty, imports)
+    }
+}
+
+/// Matches `#[derive(Serialize)]`, `#[derive(serde::Deserialize)]` and alike.
+fn is_serde_derive(attr: &Attribute) -> bool {
+    if !attr.path().is_ident("derive") {
+        return false;
+    }
+
+    let mut found = false;
+    let _ = attr.parse_nested_meta(|meta| {
+        found |=
+            meta.path.segments.last().is_some_and(|segment| {
+                segment.ident == "Serialize" || segment.ident == "Deserialize"
+            });
+        Ok(())
+    });
+    found
+}
+
+/// Matches the serde derives and `#[serde(...)]` attributes.
+pub(crate) fn is_serde_attr(attr: &Attribute) -> bool {
+    is_serde_derive(attr) || attr.path().is_ident("serde")
+}
+
+/// The `#[serde(...)]` attributes of a container, a variant or a field
+/// that affect the shape of the JSON.
+#[derive(Debug, Default)]
+struct SerdeAttrs {
+    rename: Option<String>,
+    rename_all: Option<RenameRule>,
+    rename_all_fields: Option<RenameRule>,
+    tag: Option<String>,
+    content: Option<String>,
+    untagged: bool,
+    transparent: bool,
+    flatten: bool,
+    default: bool,
+    skip: bool,
+    /// `skip_serializing`, `skip_deserializing` or `skip_serializing_if`:
+    /// the field is present in one direction only or not always.
+    skip_one_way: bool,
+    /// `with`, `serialize_with` or `deserialize_with`.
+    custom: bool,
+}
+
+impl SerdeAttrs {
+    fn parse(attrs: &[Attribute]) -> Self {
+        let mut serde = SerdeAttrs::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            let _ = attr.parse_nested_meta(|meta| {
+                let key = meta
+                    .path
+                    .get_ident()
+                    .map(|ident| ident.to_string())
+                    .unwrap_or_default();
+                match key.as_str() {
+                    "rename" => serde.rename = Some(parse_name(&meta)?),
+                    "rename_all" => serde.rename_all = RenameRule::parse(&parse_name(&meta)?),
+                    "rename_all_fields" => {
+                        serde.rename_all_fields = RenameRule::parse(&parse_name(&meta)?)
+                    }
+                    "tag" => serde.tag = Some(meta.value()?.parse::<LitStr>()?.value()),
+                    "content" => serde.content = Some(meta.value()?.parse::<LitStr>()?.value()),
+                    "untagged" => serde.untagged = true,
+                    "transparent" => serde.transparent = true,
+                    "flatten" => serde.flatten = true,
+                    "skip" => serde.skip = true,
+                    "default" => {
+                        serde.default = true;
+                        skip_value(&meta)?;
+                    }
+                    "skip_serializing" | "skip_deserializing" | "skip_serializing_if" => {
+                        serde.skip_one_way = true;
+                        skip_value(&meta)?;
+                    }
+                    "with" | "serialize_with" | "deserialize_with" => {
+                        serde.custom = true;
+                        skip_value(&meta)?;
+                    }
+                    _ => skip_value(&meta)?,
+                }
+                Ok(())
+            });
+        }
+
+        serde
+    }
+}
+
+/// Parses `= "name"` or `(serialize = "name", deserialize = "name")`,
+/// preferring the serialized name.
+fn parse_name(meta: &ParseNestedMeta) -> syn::Result<String> {
+    if meta.input.peek(Token![=]) {
+        return Ok(meta.value()?.parse::<LitStr>()?.value());
+    }
+
+    let mut serialize = None;
+    let mut deserialize = None;
+    meta.parse_nested_meta(|meta| {
+        let name = meta.value()?.parse::<LitStr>()?.value();
+        if meta.path.is_ident("serialize") {
+            serialize = Some(name);
+        } else if meta.path.is_ident("deserialize") {
+            deserialize = Some(name);
+        }
+        Ok(())
+    })?;
+    serialize
+        .or(deserialize)
+        .ok_or_else(|| meta.error("expected a name"))
+}
+
+/// Consumes the value of an attribute that doesn't matter, like `= "path"` or `(...)`.
+fn skip_value(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.input.parse::<proc_macro2::Group>()?;
+    }
+    Ok(())
+}
+
+/// A `rename_all` rule, applied the same way serde does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn parse(rule: &str) -> Option<Self> {
+        match rule {
+            "lowercase" => Some(RenameRule::Lower),
+            "UPPERCASE" => Some(RenameRule::Upper),
+            "PascalCase" => Some(RenameRule::Pascal),
+            "camelCase" => Some(RenameRule::Camel),
+            "snake_case" => Some(RenameRule::Snake),
+            "SCREAMING_SNAKE_CASE" => Some(RenameRule::ScreamingSnake),
+            "kebab-case" => Some(RenameRule::Kebab),
+            "SCREAMING-KEBAB-CASE" => Some(RenameRule::ScreamingKebab),
+            _ => None,
+        }
+    }
+
+    /// Renames a variant, which is written in PascalCase.
+    fn apply_to_variant(self, variant: &str) -> String {
+        match self {
+            RenameRule::Pascal => variant.to_string(),
+            RenameRule::Lower => variant.to_ascii_lowercase(),
+            RenameRule::Upper => variant.to_ascii_uppercase(),
+            RenameRule::Camel => {
+                let mut chars = variant.chars();
+                match chars.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                    None => String::new(),
+                }
+            }
+            RenameRule::Snake => {
+                let mut snake = String::new();
+                for (i, ch) in variant.char_indices() {
+                    if i > 0 && ch.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.push(ch.to_ascii_lowercase());
+                }
+                snake
+            }
+            RenameRule::ScreamingSnake => RenameRule::Snake
+                .apply_to_variant(variant)
+                .to_ascii_uppercase(),
+            RenameRule::Kebab => RenameRule::Snake
+                .apply_to_variant(variant)
+                .replace('_', "-"),
+            RenameRule::ScreamingKebab => RenameRule::ScreamingSnake
+                .apply_to_variant(variant)
+                .replace('_', "-"),
+        }
+    }
+
+    /// Renames a field, which is written in snake_case.
+    fn apply_to_field(self, field: &str) -> String {
+        match self {
+            RenameRule::Lower | RenameRule::Snake => field.to_string(),
+            RenameRule::Upper | RenameRule::ScreamingSnake => field.to_ascii_uppercase(),
+            RenameRule::Pascal => {
+                let mut pascal = String::new();
+                let mut capitalize = true;
+                for ch in field.chars() {
+                    if ch == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(ch.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(ch);
+                    }
+                }
+                pascal
+            }
+            RenameRule::Camel => {
+                let pascal = RenameRule::Pascal.apply_to_field(field);
+                let mut chars = pascal.chars();
+                match chars.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                    None => String::new(),
+                }
+            }
+            RenameRule::Kebab => field.replace('_', "-"),
+            RenameRule::ScreamingKebab => field.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RenameRule::{self, *};
+
+    const RULES: [RenameRule; 8] = [
+        Lower,
+        Upper,
+        Pascal,
+        Camel,
+        Snake,
+        ScreamingSnake,
+        Kebab,
+        ScreamingKebab,
+    ];
+
+    #[test]
+    fn parse() {
+        let names = [
+            "lowercase",
+            "UPPERCASE",
+            "PascalCase",
+            "camelCase",
+            "snake_case",
+            "SCREAMING_SNAKE_CASE",
+            "kebab-case",
+            "SCREAMING-KEBAB-CASE",
+        ];
+        for (name, rule) in names.into_iter().zip(RULES) {
+            assert_eq!(RenameRule::parse(name), Some(rule), "{name}");
+        }
+        assert_eq!(RenameRule::parse("Snake_Case"), None);
+    }
+
+    #[test]
+    fn variants() {
+        let cases = [
+            (
+                "Outcome",
+                [
+                    "outcome", "OUTCOME", "Outcome", "outcome", "outcome", "OUTCOME", "outcome",
+                    "OUTCOME",
+                ],
+            ),
+            (
+                "VeryTasty",
+                [
+                    "verytasty",
+                    "VERYTASTY",
+                    "VeryTasty",
+                    "veryTasty",
+                    "very_tasty",
+                    "VERY_TASTY",
+                    "very-tasty",
+                    "VERY-TASTY",
+                ],
+            ),
+            ("A", ["a", "A", "A", "a", "a", "A", "a", "A"]),
+            (
+                "Z42",
+                ["z42", "Z42", "Z42", "z42", "z42", "Z42", "z42", "Z42"],
+            ),
+        ];
+        for (variant, expected) in cases {
+            for (rule, expected) in RULES.into_iter().zip(expected) {
+                assert_eq!(
+                    rule.apply_to_variant(variant),
+                    expected,
+                    "{rule:?} {variant}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fields() {
+        let cases = [
+            (
+                "outcome",
+                [
+                    "outcome", "OUTCOME", "Outcome", "outcome", "outcome", "OUTCOME", "outcome",
+                    "OUTCOME",
+                ],
+            ),
+            (
+                "very_tasty",
+                [
+                    "very_tasty",
+                    "VERY_TASTY",
+                    "VeryTasty",
+                    "veryTasty",
+                    "very_tasty",
+                    "VERY_TASTY",
+                    "very-tasty",
+                    "VERY-TASTY",
+                ],
+            ),
+            ("a", ["a", "A", "A", "a", "a", "A", "a", "A"]),
+            (
+                "z42",
+                ["z42", "Z42", "Z42", "z42", "z42", "Z42", "z42", "Z42"],
+            ),
+        ];
+        for (field, expected) in cases {
+            for (rule, expected) in RULES.into_iter().zip(expected) {
+                assert_eq!(rule.apply_to_field(field), expected, "{rule:?} {field}");
+            }
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+use syn::{GenericArgument, PathArguments, PathSegment, Type};
+
+use crate::imports::Imports;
+
+/// The shape of a value as it crosses the IPC boundary, i.e. after serialization to JSON.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", content = "of", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum TypeRef {
+    String,
+    Number,
+    Boolean,
+    /// `()`, serialized as `null`.
+    Unit,
+    Option(Box<TypeRef>),
+    List(Box<TypeRef>),
+    Tuple(Vec<TypeRef>),
+    Map(Box<TypeRef>, Box<TypeRef>),
+    /// A `tauri::ipc::Channel` sending values of the type to the frontend.
+    Channel(Box<TypeRef>),
+    /// A type defined in the scanned sources, or a generic parameter of one.
+    Named {
+        name: String,
+        args: Vec<TypeRef>,
+    },
+    /// A type that can't be described, e.g. a type defined outside the scanned sources.
+    Any,
+}
+
+impl TypeRef {
+    /// Maps a Rust type to the shape of its serialized value,
+    /// resolving its paths with the imports of the file it's written in.
+    pub(crate) fn from_type(ty: &Type, imports: &Imports) -> Self {
+        match ty {
+            Type::Reference(reference) => Self::from_type(&reference.elem, imports),
+            Type::Paren(paren) => Self::from_type(&paren.elem, imports),
+            Type::Group(group) => Self::from_type(&group.elem, imports),
+            Type::Slice(slice) => TypeRef::List(Box::new(Self::from_type(&slice.elem, imports))),
+            Type::Array(array) => TypeRef::List(Box::new(Self::from_type(&array.elem, imports))),
+            Type::Tuple(tuple) if tuple.elems.is_empty() => TypeRef::Unit,
+            Type::Tuple(tuple) => TypeRef::Tuple(
+                tuple
+                    .elems
+                    .iter()
+                    .map(|ty| Self::from_type(ty, imports))
+                    .collect(),
+            ),
+            Type::Path(path) if path.qself.is_none() => Self::from_path(&path.path, imports),
+            _ => TypeRef::Any,
+        }
+    }
+
+    /// Maps the return type of a command. An error rejects the promise returned by `invoke`,
+    /// so for `Result<T, E>` (or an alias like `tauri::Result<T>`) only `T` is taken.
+    pub(crate) fn from_return_type(ty: &Type, imports: &Imports) -> Self {
+        Self::resolved_type(ty)
+            .map(|ty| Self::from_type(ty, imports))
+            .unwrap_or(TypeRef::Any)
+    }
+
+    /// The type of the value a command returning `ty` resolves with,
+    /// `None` for a `Result` without type arguments.
+    pub(crate) fn resolved_type(ty: &Type) -> Option<&Type> {
+        match ty {
+            Type::Path(path) if path.qself.is_none() => match path.path.segments.last() {
+                Some(segment) if segment.ident == "Result" => type_args(segment).first().copied(),
+                _ => Some(ty),
+            },
+            _ => Some(ty),
+        }
+    }
+
+    fn from_path(path: &syn::Path, imports: &Imports) -> Self {
+        let Some(segment) = path.segments.last() else {
+            return TypeRef::Any;
+        };
+        let args = type_args(segment);
+        let arg = |index: usize| {
+            args.get(index)
+                .map(|ty| Self::from_type(ty, imports))
+                .unwrap_or(TypeRef::Any)
+        };
+
+        match segment.ident.to_string().as_str() {
+            "String" | "str" | "char" | "PathBuf" | "Path" | "OsString" | "OsStr" => {
+                TypeRef::String
+            }
+            "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64"
+            | "u128" | "usize" | "f32" | "f64" => TypeRef::Number,
+            "bool" => TypeRef::Boolean,
+            "Option" => TypeRef::Option(Box::new(arg(0))),
+            "Vec" | "VecDeque" | "LinkedList" | "BinaryHeap" | "HashSet" | "BTreeSet"
+            | "IndexSet" => TypeRef::List(Box::new(arg(0))),
+            "HashMap" | "BTreeMap" | "IndexMap" => TypeRef::Map(Box::new(arg(0)), Box::new(arg(1))),
+            "Box" | "Rc" | "Arc" | "Cow" => arg(0),
+            // Not a type of the same name defined in the sources.
+            "Channel" if imports.is_from(path, "tauri") => TypeRef::Channel(Box::new(arg(0))),
+            // Any JSON value.
+            "Value" if path.segments.len() > 1 && path.segments[0].ident == "serde_json" => {
+                TypeRef::Any
+            }
+            name => TypeRef::Named {
+                name: name.to_string(),
+                args: args.iter().map(|ty| Self::from_type(ty, imports)).collect(),
+            },
+        }
+    }
+
+    /// Replaces the types that are neither in `known` nor in `generics` with [`TypeRef::Any`].
+    pub(crate) fn resolve(&mut self, known: &HashSet<String>, generics: &[String]) {
+        match self {
+            TypeRef::Option(inner) | TypeRef::List(inner) | TypeRef::Channel(inner) => {
+                inner.resolve(known, generics)
+            }
+            TypeRef::Tuple(items) => items.iter_mut().for_each(|ty| ty.resolve(known, generics)),
+            TypeRef::Map(key, value) => {
+                key.resolve(known, generics);
+                value.resolve(known, generics);
+            }
+            TypeRef::Named { name, args } if known.contains(name) || generics.contains(name) => {
+                args.iter_mut().for_each(|ty| ty.resolve(known, generics));
+            }
+            TypeRef::Named { .. } => *self = TypeRef::Any,
+            _ => {}
+        }
+    }
+
+    /// Replaces the generic parameters of a function with [`TypeRef::Any`],
+    /// as the function can be called with any type.
+    pub(crate) fn erase(&mut self, generics: &[String]) {
+        match self {
+            TypeRef::Option(inner) | TypeRef::List(inner) | TypeRef::Channel(inner) => {
+                inner.erase(generics)
+            }
+            TypeRef::Tuple(items) => items.iter_mut().for_each(|ty| ty.erase(generics)),
+            TypeRef::Map(key, value) => {
+                key.erase(generics);
+                value.erase(generics);
+            }
+            TypeRef::Named { name, .. } if generics.contains(name) => *self = TypeRef::Any,
+            TypeRef::Named { args, .. } => args.iter_mut().for_each(|ty| ty.erase(generics)),
+            _ => {}
+        }
+    }
+
+    /// Whether the type is or contains a [`TypeRef::Channel`].
+    pub(crate) fn has_channel(&self) -> bool {
+        match self {
+            TypeRef::Channel(_) => true,
+            TypeRef::Option(inner) | TypeRef::List(inner) => inner.has_channel(),
+            TypeRef::Tuple(items) => items.iter().any(TypeRef::has_channel),
+            TypeRef::Map(key, value) => key.has_channel() || value.has_channel(),
+            TypeRef::Named { args, .. } => args.iter().any(TypeRef::has_channel),
+            _ => false,
+        }
+    }
+
+    /// Calls `f` with the name of every named type this type refers to.
+    pub(crate) fn visit_names(&self, f: &mut impl FnMut(&str)) {
+        match self {
+            TypeRef::Option(inner) | TypeRef::List(inner) | TypeRef::Channel(inner) => {
+                inner.visit_names(f)
+            }
+            TypeRef::Tuple(items) => items.iter().for_each(|ty| ty.visit_names(f)),
+            TypeRef::Map(key, value) => {
+                key.visit_names(f);
+                value.visit_names(f);
+            }
+            TypeRef::Named { name, args } => {
+                f(name);
+                args.iter().for_each(|ty| ty.visit_names(f));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Types of common crates that aren't in the scanned sources, which are described
+/// as `any` without a warning when imported by their name, like `serde_json::Value`.
+pub(crate) const OPAQUE_TYPES: &[&str] = &["Value"];
+
+/// The type arguments of a path segment, e.g. `K` and `V` of `HashMap<K, V>`.
+fn type_args(segment: &PathSegment) -> Vec<&Type> {
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
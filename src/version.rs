@@ -0,0 +1,81 @@
+use std::path::Path;
+
+/// The major version of Tauri the declarations are generated for.
+///
+/// The versions differ in the module that exports [`invoke`] and in its signature:
+///
+/// * [`TauriVersion::V1`] - `@tauri-apps/api/tauri`
+/// * [`TauriVersion::V2`] - `@tauri-apps/api/core`
+///
+/// [`invoke`]: https://v2.tauri.app/reference/javascript/api/namespacecore/#invoke
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[non_exhaustive]
+pub enum TauriVersion {
+    V1,
+    #[default]
+    V2,
+}
+
+impl TauriVersion {
+    /// Determines the version from the `tauri` dependency of the crate located in `manifest_dir`.
+    ///
+    /// The version requirement in `Cargo.toml` is checked first. If it is missing
+    /// (for example, the dependency is inherited from the workspace), the version
+    /// locked in the nearest `Cargo.lock` is used.
+    pub fn detect(manifest_dir: impl AsRef<Path>) -> Option<Self> {
+        let manifest_dir = manifest_dir.as_ref();
+        from_manifest(manifest_dir).or_else(|| from_lockfile(manifest_dir))
+    }
+
+    /// The module that exports the `invoke` function.
+    pub fn module(self) -> &'static str {
+        match self {
+            TauriVersion::V1 => "@tauri-apps/api/tauri",
+            TauriVersion::V2 => "@tauri-apps/api/core",
+        }
+    }
+
+    fn from_major(major: &str) -> Option<Self> {
+        match major {
+            "1" => Some(TauriVersion::V1),
+            "2" => Some(TauriVersion::V2),
+            _ => None,
+        }
+    }
+}
+
+fn from_manifest(manifest_dir: &Path) -> Option<TauriVersion> {
+    let manifest = std::fs::read_to_string(manifest_dir.join("Cargo.toml")).ok()?;
+    let manifest = manifest.parse::<toml::Table>().ok()?;
+    let requirement = match manifest.get("dependencies")?.get("tauri")? {
+        toml::Value::String(requirement) => requirement,
+        toml::Value::Table(dependency) => dependency.get("version")?.as_str()?,
+        _ => return None,
+    };
+
+    // Requirements look like "2", "^2.0", "~1.6" or "=1.6.1".
+    let major = requirement
+        .trim_start_matches(|c: char| !c.is_ascii_digit())
+        .split('.')
+        .next()?;
+    TauriVersion::from_major(major)
+}
+
+fn from_lockfile(manifest_dir: &Path) -> Option<TauriVersion> {
+    // In a workspace the lock file lives in the workspace root.
+    let lockfile = manifest_dir
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|path| path.is_file())?;
+    let lockfile = std::fs::read_to_string(lockfile).ok()?;
+    let lockfile = lockfile.parse::<toml::Table>().ok()?;
+
+    lockfile
+        .get("package")?
+        .as_array()?
+        .iter()
+        .filter(|package| package.get("name").and_then(|name| name.as_str()) == Some("tauri"))
+        .filter_map(|package| package.get("version")?.as_str())
+        .filter_map(|version| TauriVersion::from_major(version.split('.').next()?))
+        .max()
+}
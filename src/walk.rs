@@ -0,0 +1,197 @@
+use std::path::{Component, Path, PathBuf};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+
+use crate::{config::Config, diagnostics::Diagnostic, error::Error};
+
+/// Directories that never contain the sources of the crate: build artifacts
+/// and the dependencies and bundles of the frontend.
+const DEFAULT_EXCLUDES: &[&str] = &["**/target", "**/node_modules", "**/dist"];
+
+/// Lists the `.rs` files under `root`, honouring `.gitignore` and `.ignore` files
+/// and the include/exclude globs of the config, which are relative to `root`.
+/// The entries that can't be read are skipped with a warning.
+pub(crate) fn source_files(
+    root: &Path,
+    config: &Config,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<Vec<PathBuf>, Error> {
+    let include = glob_set(config.include.iter().map(String::as_str))?;
+    let default_excludes = match config.default_excludes {
+        true => DEFAULT_EXCLUDES,
+        false => &[],
+    };
+    let exclude = glob_set(
+        default_excludes
+            .iter()
+            .copied()
+            .chain(config.exclude.iter().map(String::as_str)),
+    )?;
+
+    let walk_root = root.to_path_buf();
+    let walker = WalkBuilder::new(root)
+        // The crate isn't necessarily a git repository on its own, e.g. when it's vendored.
+        .require_git(false)
+        .filter_entry(move |entry| {
+            let path = entry
+                .path()
+                .strip_prefix(&walk_root)
+                .unwrap_or(entry.path());
+            !exclude.is_match(path)
+        })
+        .build();
+
+    let mut files = Vec::new();
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                diagnostics.push(Diagnostic::warning(format!(
+                    "Skipping an entry of {}: {}",
+                    root.display(),
+                    err
+                )));
+                continue;
+            }
+        };
+        let path = entry.path();
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        let is_file = entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_file());
+        let is_rust = path.extension().is_some_and(|extension| extension == "rs");
+        if is_file && is_rust && (config.include.is_empty() || include.is_match(relative)) {
+            files.push(path.to_path_buf());
+        }
+    }
+
+    // The order of the walk depends on the file system.
+    files.sort();
+    Ok(files)
+}
+
+/// The root files of the crate targets: the library and the binaries, as declared
+/// in `Cargo.toml` or found in the default locations.
+pub(crate) fn crate_roots(manifest_dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let manifest_path = manifest_dir.join("Cargo.toml");
+    let manifest = std::fs::read_to_string(&manifest_path).map_err(Error::io(&manifest_path))?;
+    let manifest = manifest
+        .parse::<toml::Table>()
+        .map_err(|err| Error::Parse {
+            path: manifest_path.clone(),
+            line: err
+                .span()
+                .map_or(1, |span| manifest[..span.start].matches('\n').count() + 1),
+            message: err.message().to_string(),
+        })?;
+    let target_path = |target: &toml::Value| {
+        target
+            .get("path")
+            .and_then(|path| path.as_str())
+            .map(|path| normalize(&manifest_dir.join(path)))
+    };
+
+    let mut roots = Vec::new();
+    roots.push(
+        manifest
+            .get("lib")
+            .and_then(target_path)
+            .unwrap_or_else(|| manifest_dir.join("src/lib.rs")),
+    );
+    let bins = manifest.get("bin").and_then(|bins| bins.as_array());
+    roots.extend(bins.into_iter().flatten().filter_map(target_path));
+
+    let autobins = manifest
+        .get("package")
+        .and_then(|package| package.get("autobins"))
+        .and_then(|autobins| autobins.as_bool())
+        .unwrap_or(true);
+    if autobins {
+        roots.push(manifest_dir.join("src/main.rs"));
+        if let Ok(entries) = std::fs::read_dir(manifest_dir.join("src/bin")) {
+            let mut bins = entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .map(|path| match path.is_dir() {
+                    true => path.join("main.rs"),
+                    false => path,
+                })
+                .filter(|path| path.extension().is_some_and(|extension| extension == "rs"))
+                .collect::<Vec<_>>();
+            bins.sort();
+            roots.extend(bins);
+        }
+    }
+
+    roots.retain(|root| root.is_file());
+    roots.dedup();
+    Ok(roots)
+}
+
+/// Finds the file of the module declared as `mod name;` whose submodule
+/// files are in `dir`: `dir/name.rs` or `dir/name/mod.rs`.
+pub(crate) fn module_file(dir: &Path, name: &str) -> Option<PathBuf> {
+    [
+        dir.join(format!("{name}.rs")),
+        dir.join(name).join("mod.rs"),
+    ]
+    .into_iter()
+    .find(|path| path.is_file())
+}
+
+/// Removes the `.` components of a path and resolves the `..` ones lexically,
+/// like `src/../shared/s.rs` to `shared/s.rs`, so that a file has one path.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                // `/..` is `/`.
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => normalized.push(".."),
+            },
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+fn glob_set<'a>(globs: impl IntoIterator<Item = &'a str>) -> Result<GlobSet, Error> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(Glob::new(glob).map_err(|err| Error::config(err.to_string()))?);
+    }
+    builder
+        .build()
+        .map_err(|err| Error::config(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalized_paths() {
+        assert_eq!(
+            normalize(Path::new("/app/src/../shared/./s.rs")),
+            Path::new("/app/shared/s.rs")
+        );
+        assert_eq!(
+            normalize(Path::new("/../src/lib.rs")),
+            Path::new("/src/lib.rs")
+        );
+        assert_eq!(
+            normalize(Path::new("src/a/../../../b.rs")),
+            Path::new("../b.rs")
+        );
+        assert_eq!(
+            normalize(Path::new("./src/main.rs")),
+            Path::new("src/main.rs")
+        );
+    }
+}
@@ -0,0 +1,35 @@
+use std::{fs, io, path::Path};
+
+use crate::error::Error;
+
+/// Writes a generated file, creating its directory if needed.
+///
+/// The file is left untouched when its content is the same, so that the watchers of the
+/// frontend dev server and Tauri don't reload. Otherwise the content is written to a temporary
+/// file next to it which is renamed into place, so the file is never seen half-written.
+pub(crate) fn write_if_changed(path: &Path, content: &str) -> Result<(), Error> {
+    if fs::read_to_string(path).is_ok_and(|current| current == content) {
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(Error::io(dir))?;
+    }
+    write_atomic(path, content).map_err(Error::io(path))
+}
+
+fn write_atomic(path: &Path, content: &str) -> io::Result<()> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file path"))?;
+    // In the same directory, as a rename can't cross file systems.
+    let temp = path.with_file_name(format!(
+        ".{}.{}.tmp",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+    fs::write(&temp, content)
+        .and_then(|()| fs::rename(&temp, path))
+        .inspect_err(|_| {
+            let _ = fs::remove_file(&temp);
+        })
+}